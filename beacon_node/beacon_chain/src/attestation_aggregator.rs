@@ -2,8 +2,8 @@ use log::trace;
 use state_processing::validate_attestation_without_signature;
 use std::collections::{HashMap, HashSet};
 use types::{
-    AggregateSignature, Attestation, AttestationData, BeaconState, BeaconStateError, Bitfield,
-    ChainSpec, FreeAttestation, Signature,
+    AggregatePublicKey, AggregateSignature, Attestation, AttestationData, BeaconState,
+    BeaconStateError, Bitfield, ChainSpec, FreeAttestation, Signature, Slot,
 };
 
 const PHASE_0_CUSTODY_BIT: bool = false;
@@ -47,6 +47,9 @@ pub enum Message {
     BadShard,
     /// Attestation is from the epoch prior to this, ignoring.
     TooOld,
+    /// The attested `beacon_block_root` is not yet known. The free attestation has been queued
+    /// and will be processed once the block is imported.
+    UnknownBeaconBlock,
 }
 
 macro_rules! valid_outcome {
@@ -166,6 +169,56 @@ impl AttestationAggregator {
         }
     }
 
+    /// Returns a copy of every stored `Attestation`.
+    pub fn attestations(&self) -> Vec<Attestation> {
+        self.store.values().cloned().collect()
+    }
+
+    /// Inserts some previously known `Attestation`s (e.g., loaded from disk after a restart).
+    ///
+    /// Each attestation is validated against `cached_state` with the same checks as
+    /// `process_free_attestation` (see `validate_restored_attestation`). Attestations which are
+    /// invalid, which are too old to be included in a block at `present_slot` or for which an
+    /// `Attestation` with the same data is already stored are discarded.
+    ///
+    /// Returns the number of attestations inserted.
+    pub fn restore_attestations(
+        &mut self,
+        attestations: Vec<Attestation>,
+        cached_state: &BeaconState,
+        present_slot: Slot,
+        spec: &ChainSpec,
+    ) -> Result<usize, BeaconStateError> {
+        let mut restored = 0;
+
+        for attestation in attestations {
+            if attestation.data.slot + spec.epoch_length < present_slot {
+                continue;
+            }
+
+            let signable_message = attestation.signable_message(PHASE_0_CUSTODY_BIT);
+
+            if self.store.contains_key(&signable_message) {
+                continue;
+            }
+
+            let outcome = validate_restored_attestation(cached_state, &attestation, spec)?;
+            if !outcome.valid {
+                trace!(
+                    "Discarding restored attestation, slot: {}, shard: {}",
+                    attestation.data.slot,
+                    attestation.data.shard
+                );
+                continue;
+            }
+
+            self.store.insert(signable_message, attestation);
+            restored += 1;
+        }
+
+        Ok(restored)
+    }
+
     /// Returns all known attestations which are:
     ///
     /// - Valid for the given state
@@ -196,6 +249,64 @@ impl AttestationAggregator {
     }
 }
 
+/// Checks that some (possibly aggregated) `attestation` could have been produced by
+/// `process_free_attestation` upon `cached_state`:
+///
+///  - A committee is assigned to the given `shard` at the given `slot`.
+///  - Each participant in the `aggregation_bitfield` is in that committee.
+///  - The aggregate signature is verified against the participants' public keys.
+fn validate_restored_attestation(
+    cached_state: &BeaconState,
+    attestation: &Attestation,
+    spec: &ChainSpec,
+) -> Result<Outcome, BeaconStateError> {
+    let committees =
+        match cached_state.get_crosslink_committees_at_slot(attestation.data.slot, spec) {
+            Err(BeaconStateError::EpochCacheUninitialized(e)) => {
+                panic!("Attempted to access unbuilt cache {:?}.", e)
+            }
+            Err(BeaconStateError::EpochOutOfBounds) => invalid_outcome!(Message::TooOld),
+            Err(e) => return Err(e),
+            Ok(committees) => committees,
+        };
+
+    let committee = match committees
+        .iter()
+        .find(|(_, shard)| *shard == attestation.data.shard)
+    {
+        None => invalid_outcome!(Message::BadShard),
+        Some((committee, _)) => committee,
+    };
+
+    let bitfield = &attestation.aggregation_bitfield;
+    match bitfield.highest_set_bit() {
+        Some(i) if i < committee.len() => (),
+        _ => invalid_outcome!(Message::BadValidatorIndex),
+    }
+
+    let mut group_public_key = AggregatePublicKey::new();
+    for (i, validator_index) in committee.iter().enumerate() {
+        if bitfield.get(i).unwrap_or(false) {
+            match cached_state.validator_registry.get(*validator_index) {
+                None => invalid_outcome!(Message::BadValidatorIndex),
+                Some(validator_record) => group_public_key.add(validator_record.pubkey.as_raw()),
+            }
+        }
+    }
+
+    if !attestation.verify_signature(
+        &group_public_key,
+        PHASE_0_CUSTODY_BIT,
+        cached_state
+            .fork
+            .get_domain(cached_state.current_epoch(spec), spec.domain_attestation),
+    ) {
+        invalid_outcome!(Message::BadSignature);
+    }
+
+    valid_outcome!(Message::NewAttestationCreated);
+}
+
 /// Produces a new `Attestation` where:
 ///
 /// - `signature` is added to `Attestation.aggregate_signature`
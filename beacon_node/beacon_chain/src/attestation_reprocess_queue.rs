@@ -0,0 +1,160 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use types::{FreeAttestation, Hash256};
+
+/// Buffers `FreeAttestation`s which reference a block that has not yet been imported, so they may
+/// be processed once that block arrives (rather than being dropped).
+///
+/// Attestations are held for at most `timeout`. If the queue is full, the oldest attestation is
+/// evicted to make room for a new one.
+pub struct AttestationReprocessQueue {
+    queue: VecDeque<(Instant, FreeAttestation)>,
+    max_len: usize,
+    timeout: Duration,
+}
+
+impl AttestationReprocessQueue {
+    /// Instantiates a new, empty queue.
+    pub fn new(max_len: usize, timeout: Duration) -> Self {
+        Self {
+            queue: VecDeque::new(),
+            max_len,
+            timeout,
+        }
+    }
+
+    /// Queue `free_attestation`, which was received at `now`.
+    ///
+    /// Returns the number of previously queued attestations which were dropped, either because
+    /// they expired or to make room.
+    pub fn push(&mut self, free_attestation: FreeAttestation, now: Instant) -> usize {
+        let mut dropped = self.prune(now);
+
+        if self.max_len == 0 {
+            return dropped;
+        }
+
+        while self.queue.len() >= self.max_len {
+            self.queue.pop_front();
+            dropped += 1;
+        }
+
+        self.queue.push_back((now, free_attestation));
+
+        dropped
+    }
+
+    /// Removes and returns all unexpired attestations which reference `block_root`, in the order
+    /// they were queued.
+    pub fn pop_for_block(&mut self, block_root: &Hash256, now: Instant) -> Vec<FreeAttestation> {
+        self.prune(now);
+
+        let (matching, remaining): (Vec<_>, VecDeque<_>) = self
+            .queue
+            .drain(..)
+            .partition(|(_, a)| a.data.beacon_block_root == *block_root);
+        self.queue = remaining;
+
+        matching.into_iter().map(|(_, a)| a).collect()
+    }
+
+    /// Returns the number of queued attestations (including any which have expired but are yet to
+    /// be pruned).
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if there are no queued attestations.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Removes all attestations which were queued more than `timeout` prior to `now`, returning
+    /// the number removed.
+    fn prune(&mut self, now: Instant) -> usize {
+        let mut pruned = 0;
+        while let Some((received, _)) = self.queue.front() {
+            if now.duration_since(*received) <= self.timeout {
+                break;
+            }
+            self.queue.pop_front();
+            pruned += 1;
+        }
+        pruned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::test_utils::{SeedableRng, TestRandom, XorShiftRng};
+    use types::{AttestationData, Signature};
+
+    fn free_attestation(rng: &mut XorShiftRng, beacon_block_root: Hash256) -> FreeAttestation {
+        let mut data = AttestationData::random_for_test(rng);
+        data.beacon_block_root = beacon_block_root;
+        FreeAttestation {
+            data,
+            signature: Signature::random_for_test(rng),
+            validator_index: 0,
+        }
+    }
+
+    #[test]
+    fn pop_for_block() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut queue = AttestationReprocessQueue::new(8, Duration::from_secs(10));
+        let now = Instant::now();
+
+        let root_a = Hash256::from(&[1; 32][..]);
+        let root_b = Hash256::from(&[2; 32][..]);
+        let a_1 = free_attestation(&mut rng, root_a);
+        let b_1 = free_attestation(&mut rng, root_b);
+        let a_2 = free_attestation(&mut rng, root_a);
+
+        assert_eq!(queue.push(a_1.clone(), now), 0);
+        assert_eq!(queue.push(b_1.clone(), now), 0);
+        assert_eq!(queue.push(a_2.clone(), now), 0);
+
+        assert_eq!(queue.pop_for_block(&root_a, now), vec![a_1, a_2]);
+        assert_eq!(queue.len(), 1);
+        assert!(queue.pop_for_block(&root_a, now).is_empty());
+        assert_eq!(queue.pop_for_block(&root_b, now), vec![b_1]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn evicts_oldest_when_full() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut queue = AttestationReprocessQueue::new(2, Duration::from_secs(10));
+        let now = Instant::now();
+
+        let root = Hash256::from(&[1; 32][..]);
+        let attestations: Vec<FreeAttestation> =
+            (0..3).map(|_| free_attestation(&mut rng, root)).collect();
+
+        assert_eq!(queue.push(attestations[0].clone(), now), 0);
+        assert_eq!(queue.push(attestations[1].clone(), now), 0);
+        assert_eq!(queue.push(attestations[2].clone(), now), 1);
+
+        assert_eq!(queue.pop_for_block(&root, now), attestations[1..].to_vec());
+    }
+
+    #[test]
+    fn expires_after_timeout() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut queue = AttestationReprocessQueue::new(8, Duration::from_secs(10));
+        let now = Instant::now();
+
+        let root = Hash256::from(&[1; 32][..]);
+        let old = free_attestation(&mut rng, root);
+        let new = free_attestation(&mut rng, root);
+
+        queue.push(old, now);
+        assert_eq!(queue.push(new.clone(), now + Duration::from_secs(11)), 1);
+        assert_eq!(
+            queue.pop_for_block(&root, now + Duration::from_secs(20)),
+            vec![new]
+        );
+    }
+}
@@ -1,18 +1,24 @@
-use crate::attestation_aggregator::{AttestationAggregator, Outcome as AggregationOutcome};
+use crate::attestation_aggregator::{
+    AttestationAggregator, Message as AggregationMessage, Outcome as AggregationOutcome,
+};
+use crate::attestation_reprocess_queue::AttestationReprocessQueue;
 use crate::checkpoint::CheckPoint;
+use crate::early_block_queue::EarlyBlockQueue;
 use db::{
-    stores::{BeaconBlockStore, BeaconStateStore},
+    stores::{BeaconBlockStore, BeaconStateStore, OpPoolStore, ReorgEvent, ReorgStore},
     ClientDB, DBError,
 };
 use fork_choice::{ForkChoice, ForkChoiceError};
-use log::{debug, trace};
+use log::{debug, info, trace, warn};
 use parking_lot::{RwLock, RwLockReadGuard};
 use slot_clock::SlotClock;
 use ssz::ssz_encode;
 use state_processing::{
-    BlockProcessable, BlockProcessingError, SlotProcessable, SlotProcessingError,
+    BlockProcessingError, BlockReplayError, BlockReplayer, SlotProcessable, SlotProcessingError,
 };
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use types::{
     readers::{BeaconBlockReader, BeaconStateReader},
     AttestationData, BeaconBlock, BeaconBlockBody, BeaconState, BeaconStateError, ChainSpec,
@@ -20,6 +26,18 @@ use types::{
     Signature, Slot,
 };
 
+/// The maximum number of attestations referencing unknown blocks which are held for reprocessing.
+const MAX_QUEUED_ATTESTATIONS: usize = 1_024;
+/// How long an attestation referencing an unknown block is held for reprocessing.
+const QUEUED_ATTESTATION_TIMEOUT_SECS: u64 = 12;
+/// The maximum number of blocks which arrived before their slot that are held for processing, per
+/// slot.
+const MAX_EARLY_BLOCKS_PER_SLOT: usize = 4;
+/// How many slots after the present slot a block may be and still be queued, rather than rejected.
+const MAX_EARLY_BLOCK_SLOTS: u64 = 1;
+/// The maximum number of reorg events retained (and persisted) for later analysis.
+const MAX_RECORDED_REORGS: usize = 256;
+
 #[derive(Debug, PartialEq)]
 pub enum Error {
     InsufficientValidators,
@@ -30,6 +48,16 @@ pub enum Error {
     ForkChoiceError(ForkChoiceError),
     MissingBeaconBlock(Hash256),
     MissingBeaconState(Hash256),
+    SlotProcessingError(SlotProcessingError),
+    /// The supplied genesis state is not at the genesis slot.
+    NotGenesisState(Slot),
+    /// The epoch is prior to `spec.genesis_epoch`.
+    EpochBeforeGenesis(Epoch),
+    /// The canonical chain does not include the weak subjectivity checkpoint root at its epoch.
+    WeakSubjectivityConflict {
+        epoch: Epoch,
+        canonical_root: Hash256,
+    },
 }
 
 #[derive(Debug, PartialEq)]
@@ -46,6 +74,8 @@ pub enum InvalidBlock {
     StateRootMismatch,
     /// The blocks parent_root is unknown.
     ParentUnknown,
+    /// The block is on a chain which does not include the weak subjectivity checkpoint.
+    WeakSubjectivityConflict,
     /// There was an error whilst advancing the parent state to the present slot. This condition
     /// should not occur, it likely represents an internal error.
     SlotProcessingError(SlotProcessingError),
@@ -57,6 +87,10 @@ pub enum InvalidBlock {
 pub enum BlockProcessingOutcome {
     /// The block was successfully validated.
     ValidBlock(ValidBlock),
+    /// The block is for a slot shortly after the present slot (as read from the slot clock). It
+    /// has not been validated and will be processed by `process_early_blocks` once its slot
+    /// starts.
+    Queued,
     /// The block was not successfully validated.
     InvalidBlock(InvalidBlock),
 }
@@ -65,9 +99,19 @@ pub struct BeaconChain<T: ClientDB + Sized, U: SlotClock, F: ForkChoice> {
     pub block_store: Arc<BeaconBlockStore<T>>,
     pub state_store: Arc<BeaconStateStore<T>>,
     pub slot_clock: U,
+    /// The root of the genesis block, which identifies the chain.
+    pub genesis_block_root: Hash256,
     pub attestation_aggregator: RwLock<AttestationAggregator>,
-    canonical_head: RwLock<CheckPoint>,
+    attestation_reprocess_queue: RwLock<AttestationReprocessQueue>,
+    early_block_queue: RwLock<EarlyBlockQueue>,
+    /// A snapshot of the canonical head, replaced (rather than mutated) whenever the head changes
+    /// so that readers only hold the lock for as long as it takes to clone the `Arc`.
+    canonical_head: RwLock<Arc<CheckPoint>>,
     finalized_head: RwLock<CheckPoint>,
+    /// A block root and epoch which every chain must include (see `set_wss_checkpoint`).
+    wss_checkpoint: RwLock<Option<(Hash256, Epoch)>>,
+    /// The most recent reorgs, oldest first.
+    reorgs: RwLock<VecDeque<ReorgEvent>>,
     pub state: RwLock<BeaconState>,
     pub spec: ChainSpec,
     pub fork_choice: RwLock<F>,
@@ -94,12 +138,40 @@ where
             return Err(Error::InsufficientValidators);
         }
 
-        let mut genesis_state = BeaconState::genesis(
+        let genesis_state = BeaconState::genesis(
             genesis_time,
             initial_validator_deposits,
             latest_eth1_data,
             &spec,
         )?;
+
+        Self::from_genesis_state(
+            state_store,
+            block_store,
+            slot_clock,
+            genesis_state,
+            spec,
+            fork_choice,
+        )
+    }
+
+    /// Instantiate a new Beacon Chain from an existing genesis state (e.g., one exported by
+    /// another node).
+    pub fn from_genesis_state(
+        state_store: Arc<BeaconStateStore<T>>,
+        block_store: Arc<BeaconBlockStore<T>>,
+        slot_clock: U,
+        mut genesis_state: BeaconState,
+        spec: ChainSpec,
+        fork_choice: F,
+    ) -> Result<Self, Error> {
+        if genesis_state.slot != spec.genesis_slot {
+            return Err(Error::NotGenesisState(genesis_state.slot));
+        }
+        if genesis_state.validator_registry.is_empty() {
+            return Err(Error::InsufficientValidators);
+        }
+
         let state_root = genesis_state.canonical_root();
         state_store.put(&state_root, &ssz_encode(&genesis_state)[..])?;
 
@@ -114,14 +186,20 @@ where
             genesis_state.clone(),
             state_root,
         ));
-        let canonical_head = RwLock::new(CheckPoint::new(
+        let canonical_head = RwLock::new(Arc::new(CheckPoint::new(
             genesis_block.clone(),
             block_root,
             // TODO: this is a memory waste; remove full clone.
             genesis_state.clone(),
             state_root,
-        ));
+        )));
         let attestation_aggregator = RwLock::new(AttestationAggregator::new());
+        let attestation_reprocess_queue = RwLock::new(AttestationReprocessQueue::new(
+            MAX_QUEUED_ATTESTATIONS,
+            Duration::from_secs(QUEUED_ATTESTATION_TIMEOUT_SECS),
+        ));
+
+        let early_block_queue = RwLock::new(EarlyBlockQueue::new(MAX_EARLY_BLOCKS_PER_SLOT));
 
         genesis_state.build_epoch_cache(RelativeEpoch::Previous, &spec)?;
         genesis_state.build_epoch_cache(RelativeEpoch::Current, &spec)?;
@@ -131,15 +209,31 @@ where
             block_store,
             state_store,
             slot_clock,
+            genesis_block_root: block_root,
             attestation_aggregator,
+            attestation_reprocess_queue,
+            early_block_queue,
             state: RwLock::new(genesis_state),
             finalized_head,
             canonical_head,
+            wss_checkpoint: RwLock::new(None),
+            reorgs: RwLock::new(VecDeque::new()),
             spec,
             fork_choice: RwLock::new(fork_choice),
         })
     }
 
+    /// Returns the state of the genesis block, as read from the database.
+    pub fn genesis_state(&self) -> Result<BeaconState, Error> {
+        let genesis_block = self
+            .block_store
+            .get_deserialized(&self.genesis_block_root)?
+            .ok_or_else(|| Error::MissingBeaconBlock(self.genesis_block_root))?;
+        self.state_store
+            .get_deserialized(&genesis_block.state_root)?
+            .ok_or_else(|| Error::MissingBeaconState(genesis_block.state_root))
+    }
+
     /// Update the canonical head to some new values.
     pub fn update_canonical_head(
         &self,
@@ -152,23 +246,28 @@ where
             "Updating canonical head with block at slot: {}",
             new_beacon_block.slot
         );
-        let mut head = self.canonical_head.write();
-        head.update(
+        // Build the new snapshot before taking the lock so the write lock is only held for the
+        // swap.
+        let new_head = Arc::new(CheckPoint::new(
             new_beacon_block,
             new_beacon_block_root,
             new_beacon_state,
             new_beacon_state_root,
-        );
+        ));
+        *self.canonical_head.write() = new_head;
     }
 
-    /// Returns a read-lock guarded `CheckPoint` struct for reading the head (as chosen by the
-    /// fork-choice rule).
+    /// Returns a snapshot of the head (as chosen by the fork-choice rule).
+    ///
+    /// The snapshot does not hold any lock; it will not reflect any head updates that occur after
+    /// it is returned. Callers which need several fields to be consistent should call this once
+    /// and read from the returned value.
     ///
     /// It is important to note that the `beacon_state` returned may not match the present slot. It
     /// is the state as it was when the head block was recieved, which could be some slots prior to
     /// now.
-    pub fn head(&self) -> RwLockReadGuard<CheckPoint> {
-        self.canonical_head.read()
+    pub fn head(&self) -> Arc<CheckPoint> {
+        self.canonical_head.read().clone()
     }
 
     /// Update the justified head to some new values.
@@ -215,6 +314,71 @@ where
         Ok(())
     }
 
+    /// Returns the root of the canonical block at the start of `epoch` or, if that slot was skipped,
+    /// the latest canonical block prior to it.
+    ///
+    /// Returns `None` if the canonical head has not yet reached the start of `epoch`, as the block
+    /// at that epoch is not yet known.
+    pub fn block_root_at_epoch(&self, epoch: Epoch) -> Result<Option<Hash256>, Error> {
+        if epoch < self.spec.genesis_epoch {
+            return Err(Error::EpochBeforeGenesis(epoch));
+        }
+
+        let slot = epoch.start_slot(self.spec.epoch_length);
+        let head = self.head();
+
+        if head.beacon_block.slot < slot {
+            return Ok(None);
+        }
+
+        let root = self
+            .block_store
+            .block_root_at_or_before_slot(&head.beacon_block_root, slot)
+            .map_err(|e| Error::DBInconsistent(format!("{:?}", e)))?;
+
+        Ok(Some(root))
+    }
+
+    /// Require that the chain includes the block `root` at `epoch` (or, if that slot was skipped,
+    /// as the latest block prior to it).
+    ///
+    /// If the canonical head has reached `epoch` the checkpoint is verified immediately and `true`
+    /// is returned. Otherwise `false` is returned and any block which crosses `epoch` on a chain
+    /// without `root` is rejected as `InvalidBlock::WeakSubjectivityConflict`.
+    pub fn set_wss_checkpoint(&self, root: Hash256, epoch: Epoch) -> Result<bool, Error> {
+        let verified = match self.block_root_at_epoch(epoch)? {
+            Some(canonical_root) if canonical_root != root => {
+                return Err(Error::WeakSubjectivityConflict {
+                    epoch,
+                    canonical_root,
+                });
+            }
+            Some(_) => true,
+            None => false,
+        };
+
+        *self.wss_checkpoint.write() = Some((root, epoch));
+
+        Ok(verified)
+    }
+
+    /// Returns `true` if the present epoch (as read from the slot clock) is within the weak
+    /// subjectivity period of the finalized state.
+    ///
+    /// If it is not, validators may have exited since the finalized state and a chain built from
+    /// it is not safe to follow without some trusted, recent checkpoint.
+    pub fn is_within_weak_subjectivity_period(&self) -> bool {
+        let present_epoch = match self.read_slot_clock() {
+            Some(slot) => slot.epoch(self.spec.epoch_length),
+            None => return true,
+        };
+
+        let finalized_state = &self.finalized_head().beacon_state;
+        let ws_period = finalized_state.weak_subjectivity_period(&self.spec);
+
+        present_epoch <= finalized_state.current_epoch(&self.spec) + ws_period
+    }
+
     /// Returns the validator index (if any) for the given public key.
     ///
     /// Information is retrieved from the present `beacon_state.validator_registry`.
@@ -341,10 +505,33 @@ where
     ///
     /// - Create a new `Attestation`.
     /// - Aggregate it to an existing `Attestation`.
+    ///
+    /// If the attestation references a block which is not yet known, it is queued and processed
+    /// once that block is imported.
     pub fn process_free_attestation(
         &self,
         free_attestation: FreeAttestation,
     ) -> Result<AggregationOutcome, Error> {
+        if !self
+            .block_store
+            .exists(&free_attestation.data.beacon_block_root)?
+        {
+            let dropped = self
+                .attestation_reprocess_queue
+                .write()
+                .push(free_attestation, Instant::now());
+            if dropped > 0 {
+                debug!(
+                    "Dropped {} attestation(s) from the reprocess queue.",
+                    dropped
+                );
+            }
+            return Ok(AggregationOutcome {
+                valid: false,
+                message: AggregationMessage::UnknownBeaconBlock,
+            });
+        }
+
         let aggregation_outcome = self
             .attestation_aggregator
             .write()
@@ -364,6 +551,86 @@ where
         Ok(aggregation_outcome)
     }
 
+    /// Writes all attestations held by the `attestation_aggregator` to the `op_pool_store`, so
+    /// they may be restored after a restart.
+    pub fn persist_op_pool(&self, op_pool_store: &OpPoolStore<T>) -> Result<(), Error> {
+        let attestations = self.attestation_aggregator.read().attestations();
+        op_pool_store.put_attestations(&self.genesis_block_root, &attestations)?;
+
+        debug!(
+            "Persisted {} attestation(s) to the op pool store.",
+            attestations.len()
+        );
+
+        Ok(())
+    }
+
+    /// Loads the attestations written by `persist_op_pool` into the `attestation_aggregator`.
+    ///
+    /// Each attestation is re-validated against the present state, as a `FreeAttestation` would
+    /// be. Attestations which are invalid, or which are too old to be included in a block at the
+    /// slot read from the slot clock, are discarded. Returns the number of attestations restored.
+    ///
+    /// Note: only attestations from the epochs cached by the present state (i.e., the previous,
+    /// current and next epochs) can be validated; all others are discarded.
+    pub fn restore_op_pool(&self, op_pool_store: &OpPoolStore<T>) -> Result<usize, Error> {
+        let attestations = op_pool_store.get_attestations(&self.genesis_block_root)?;
+
+        let present_slot = self
+            .read_slot_clock()
+            .unwrap_or_else(|| self.present_slot());
+
+        let restored = self.attestation_aggregator.write().restore_attestations(
+            attestations,
+            &self.state.read(),
+            present_slot,
+            &self.spec,
+        )?;
+
+        Ok(restored)
+    }
+
+    /// Writes the recorded reorg events to the `reorg_store`, so they may be restored after a
+    /// restart.
+    pub fn persist_reorgs(&self, reorg_store: &ReorgStore<T>) -> Result<(), Error> {
+        let reorgs: Vec<ReorgEvent> = self.reorgs.read().iter().cloned().collect();
+        reorg_store.put_reorgs(&self.genesis_block_root, &reorgs)?;
+
+        debug!(
+            "Persisted {} reorg event(s) to the reorg store.",
+            reorgs.len()
+        );
+
+        Ok(())
+    }
+
+    /// Loads the reorg events written by `persist_reorgs`, placing them before any reorgs recorded
+    /// since startup. Returns the number of events restored.
+    pub fn restore_reorgs(&self, reorg_store: &ReorgStore<T>) -> Result<usize, Error> {
+        let restored = reorg_store.get_reorgs(&self.genesis_block_root)?;
+        let count = restored.len();
+
+        let mut reorgs = self.reorgs.write();
+        for reorg in restored.into_iter().rev() {
+            reorgs.push_front(reorg);
+        }
+        while reorgs.len() > MAX_RECORDED_REORGS {
+            reorgs.pop_front();
+        }
+
+        Ok(count)
+    }
+
+    /// Returns the recorded reorg events with a new head in or after `epoch`, oldest first.
+    pub fn reorgs_since(&self, epoch: Epoch) -> Vec<ReorgEvent> {
+        self.reorgs
+            .read()
+            .iter()
+            .filter(|reorg| reorg.slot.epoch(self.spec.epoch_length) >= epoch)
+            .cloned()
+            .collect()
+    }
+
     /// Dumps the entire canonical chain, from the head to genesis to a vector for analysis.
     ///
     /// This could be a very expensive operation and should only be done in testing/analysis
@@ -371,12 +638,7 @@ where
     pub fn chain_dump(&self) -> Result<Vec<CheckPoint>, Error> {
         let mut dump = vec![];
 
-        let mut last_slot = CheckPoint {
-            beacon_block: self.head().beacon_block.clone(),
-            beacon_block_root: self.head().beacon_block_root,
-            beacon_state: self.head().beacon_state.clone(),
-            beacon_state_root: self.head().beacon_state_root,
-        };
+        let mut last_slot = (*self.head()).clone();
 
         dump.push(last_slot.clone());
 
@@ -423,6 +685,37 @@ where
 
         let block_root = block.canonical_root();
 
+        // A block which is slightly ahead of the slot clock (e.g., due to clock skew) is queued
+        // until its slot starts. Its parent and proposer signature are checked first, so that only
+        // the expected proposer may fill the queue for a slot.
+        if let Some(clock_slot) = self.read_slot_clock() {
+            if block.slot > clock_slot {
+                if block.slot > clock_slot + MAX_EARLY_BLOCK_SLOTS {
+                    return Ok(BlockProcessingOutcome::InvalidBlock(
+                        InvalidBlock::FutureSlot,
+                    ));
+                }
+                if !self.block_store.exists(&block.parent_root)? {
+                    return Ok(BlockProcessingOutcome::InvalidBlock(
+                        InvalidBlock::ParentUnknown,
+                    ));
+                }
+                if !self.is_signed_by_proposer(&block)? {
+                    return Ok(BlockProcessingOutcome::InvalidBlock(
+                        InvalidBlock::PerBlockProcessingError(
+                            BlockProcessingError::BadBlockSignature,
+                        ),
+                    ));
+                }
+                if self.early_block_queue.write().push(block_root, block) {
+                    return Ok(BlockProcessingOutcome::Queued);
+                }
+                return Ok(BlockProcessingOutcome::InvalidBlock(
+                    InvalidBlock::FutureSlot,
+                ));
+            }
+        }
+
         let present_slot = self.present_slot();
 
         if block.slot > present_slot {
@@ -443,6 +736,25 @@ where
             }
         };
 
+        // Reject the block if it is the first on its chain to reach the weak subjectivity
+        // checkpoint epoch and that chain does not include the checkpoint root. Later blocks
+        // descend from a block which has already passed this check.
+        if let Some((checkpoint_root, checkpoint_epoch)) = *self.wss_checkpoint.read() {
+            let checkpoint_slot = checkpoint_epoch.start_slot(self.spec.epoch_length);
+            if parent_block.slot() < checkpoint_slot && checkpoint_slot <= block.slot {
+                let root_at_checkpoint = if block.slot == checkpoint_slot {
+                    block_root
+                } else {
+                    parent_block_root
+                };
+                if root_at_checkpoint != checkpoint_root {
+                    return Ok(BlockProcessingOutcome::InvalidBlock(
+                        InvalidBlock::WeakSubjectivityConflict,
+                    ));
+                }
+            }
+        }
+
         // Load the parent blocks state from the database, returning an error if it is not found.
         // It is an error because if know the parent block we should also know the parent state.
         let parent_state_root = parent_block.state_root();
@@ -458,31 +770,26 @@ where
         // TODO: check the block proposer signature BEFORE doing a state transition. This will
         // significantly lower exposure surface to DoS attacks.
 
-        // Transition the parent state to the present slot.
-        let mut state = parent_state;
-        for _ in state.slot.as_u64()..present_slot.as_u64() {
-            if let Err(e) = state.per_slot_processing(parent_block_root, &self.spec) {
-                return Ok(BlockProcessingOutcome::InvalidBlock(
-                    InvalidBlock::SlotProcessingError(e),
-                ));
-            }
-        }
-
-        // Apply the received block to its parent state (which has been transitioned into this
-        // slot).
-        if let Err(e) = state.per_block_processing(&block, &self.spec) {
-            return Ok(BlockProcessingOutcome::InvalidBlock(
-                InvalidBlock::PerBlockProcessingError(e),
-            ));
+        // Transition the parent state to the present slot, then apply the received block to it.
+        let mut replayer = BlockReplayer::new(parent_state, &self.spec);
+        let replay_result = replayer
+            .apply_slots(parent_block_root, present_slot)
+            .and_then(|_| replayer.apply_block(&block));
+
+        if let Err(e) = replay_result {
+            let invalid_block = match e {
+                BlockReplayError::SlotProcessingError(e) => InvalidBlock::SlotProcessingError(e),
+                BlockReplayError::BlockProcessingError(e) => {
+                    InvalidBlock::PerBlockProcessingError(e)
+                }
+                BlockReplayError::StateRootMismatch { .. } => InvalidBlock::StateRootMismatch,
+            };
+            return Ok(BlockProcessingOutcome::InvalidBlock(invalid_block));
         }
 
-        let state_root = state.canonical_root();
-
-        if block.state_root != state_root {
-            return Ok(BlockProcessingOutcome::InvalidBlock(
-                InvalidBlock::StateRootMismatch,
-            ));
-        }
+        let state = replayer.into_state();
+        // The replayer has verified the block `state_root` matches the state.
+        let state_root = block.state_root;
 
         // Store the block and state.
         self.block_store.put(&block_root, &ssz_encode(&block)[..])?;
@@ -503,9 +810,62 @@ where
             *self.state.write() = state.clone();
         }
 
+        // Process any attestations which arrived before this block.
+        let queued_attestations = self
+            .attestation_reprocess_queue
+            .write()
+            .pop_for_block(&block_root, Instant::now());
+        for free_attestation in queued_attestations {
+            if let Err(e) = self.process_free_attestation(free_attestation) {
+                debug!("Failed to reprocess queued attestation: {:?}", e);
+            }
+        }
+
         Ok(BlockProcessingOutcome::ValidBlock(ValidBlock::Processed))
     }
 
+    /// Process each queued block whose slot has started (see `BlockProcessingOutcome::Queued`).
+    ///
+    /// Should be called at the start of each slot. The present state is first advanced to the slot
+    /// read from the slot clock (see `advance_state`), otherwise the blocks would be rejected as
+    /// `InvalidBlock::FutureSlot`. Returns the outcome for each processed block.
+    ///
+    /// Note: the beacon node does not yet pass received blocks to `process_block`, so presently
+    /// only the test harness calls this function.
+    pub fn process_early_blocks(&self) -> Result<Vec<BlockProcessingOutcome>, Error> {
+        let clock_slot = match self.read_slot_clock() {
+            Some(slot) => slot,
+            None => return Ok(vec![]),
+        };
+        self.advance_state(clock_slot)?;
+
+        let ready = self.early_block_queue.write().pop_ready(clock_slot);
+
+        ready
+            .into_iter()
+            .map(|block| self.process_block(block))
+            .collect()
+    }
+
+    /// Returns `true` if `block` is signed by the proposer for its slot.
+    ///
+    /// The proposer is read from the present state, so only blocks from the present and adjacent
+    /// epochs may be checked.
+    fn is_signed_by_proposer(&self, block: &BeaconBlock) -> Result<bool, Error> {
+        let state = self.state.read();
+        let proposer_index = state.get_beacon_proposer_index(block.slot, &self.spec)?;
+        let domain = state.fork.get_domain(
+            block.slot.epoch(self.spec.epoch_length),
+            self.spec.domain_proposal,
+        );
+
+        Ok(block.signature.verify(
+            &block.proposal_root(&self.spec)[..],
+            domain,
+            &state.validator_registry[proposer_index].pubkey,
+        ))
+    }
+
     /// Produce a new block at the present slot.
     ///
     /// The produced block will not be inherently valid, it must be signed by a block producer.
@@ -513,7 +873,7 @@ where
     pub fn produce_block(&self, randao_reveal: Signature) -> Option<(BeaconBlock, BeaconState)> {
         debug!("Producing block at slot {}...", self.state.read().slot);
 
-        let mut state = self.state.read().clone();
+        let state = self.state.read().clone();
 
         trace!("Finding attestations for new block...");
 
@@ -551,14 +911,19 @@ where
 
         trace!("BeaconChain::produce_block: updating state for new block.",);
 
-        let result =
-            state.per_block_processing_without_verifying_block_signature(&block, &self.spec);
+        // The block is not yet signed and its state root is not yet known.
+        let mut replayer = BlockReplayer::new(state, &self.spec)
+            .no_block_signature_verification()
+            .no_state_root_verification();
+        let result = replayer.apply_block(&block);
         trace!(
             "BeaconNode::produce_block: state processing result: {:?}",
             result
         );
         result.ok()?;
 
+        let state = replayer.into_state();
+
         let state_root = state.canonical_root();
 
         block.state_root = state_root;
@@ -590,11 +955,86 @@ where
                 .ok_or_else(|| Error::MissingBeaconState(block.state_root))?;
             let state_root = state.canonical_root();
 
+            let old_head = self.head();
+            if old_head.beacon_block_root != block_root {
+                // Reorg analysis must not prevent the head from being updated.
+                if let Err(e) = self.record_reorg(&old_head, &block, block_root) {
+                    warn!("Unable to record reorg to {}: {:?}", block_root, e);
+                }
+            }
+
             self.update_canonical_head(block, block_root, state, state_root);
         }
 
         Ok(())
     }
+
+    /// Records a reorg if `new_head` does not descend from `old_head`.
+    fn record_reorg(
+        &self,
+        old_head: &CheckPoint,
+        new_head: &BeaconBlock,
+        new_head_root: Hash256,
+    ) -> Result<(), Error> {
+        let mut old = (
+            old_head.beacon_block_root,
+            old_head.beacon_block.slot,
+            old_head.beacon_block.parent_root,
+        );
+        let mut new = (new_head_root, new_head.slot, new_head.parent_root);
+        let mut orphaned_block_roots = vec![];
+
+        // Walk both chains back, always stepping the higher of the two, until they meet.
+        while old.0 != new.0 {
+            if old.1 >= new.1 {
+                orphaned_block_roots.push(old.0);
+                old = self.block_root_slot_and_parent(old.2)?;
+            } else {
+                new = self.block_root_slot_and_parent(new.2)?;
+            }
+        }
+
+        if orphaned_block_roots.is_empty() {
+            // The new head descends from the old head, this is not a reorg.
+            return Ok(());
+        }
+
+        let reorg = ReorgEvent {
+            slot: new_head.slot,
+            old_head_root: old_head.beacon_block_root,
+            new_head_root,
+            common_ancestor_root: old.0,
+            orphaned_block_roots,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        };
+
+        info!(
+            "Chain reorg of depth {} at slot {}, new head: {}.",
+            reorg.depth(),
+            reorg.slot,
+            new_head_root
+        );
+
+        let mut reorgs = self.reorgs.write();
+        if reorgs.len() >= MAX_RECORDED_REORGS {
+            reorgs.pop_front();
+        }
+        reorgs.push_back(reorg);
+
+        Ok(())
+    }
+
+    /// Returns the root, slot and parent root of the block with the given `root`.
+    fn block_root_slot_and_parent(&self, root: Hash256) -> Result<(Hash256, Slot, Hash256), Error> {
+        let block = self
+            .block_store
+            .get_deserialized(&root)?
+            .ok_or_else(|| Error::MissingBeaconBlock(root))?;
+        Ok((root, block.slot, block.parent_root))
+    }
 }
 
 impl From<DBError> for Error {
@@ -609,6 +1049,12 @@ impl From<ForkChoiceError> for Error {
     }
 }
 
+impl From<SlotProcessingError> for Error {
+    fn from(e: SlotProcessingError) -> Error {
+        Error::SlotProcessingError(e)
+    }
+}
+
 impl From<BeaconStateError> for Error {
     fn from(e: BeaconStateError) -> Error {
         Error::BeaconStateError(e)
@@ -0,0 +1,131 @@
+use std::collections::{BTreeMap, HashSet};
+use types::{BeaconBlock, Hash256, Slot};
+
+/// Buffers `BeaconBlock`s which arrived shortly before the start of their slot (e.g., due to
+/// clock skew), so they may be processed once that slot starts (rather than being rejected).
+///
+/// Blocks are keyed by their root, so a block which is already queued is not queued again. At
+/// most `max_per_slot` blocks are queued for each slot; once a slot is full, further blocks for
+/// that slot are refused whilst other slots are unaffected.
+pub struct EarlyBlockQueue {
+    blocks: BTreeMap<Slot, Vec<(Hash256, BeaconBlock)>>,
+    roots: HashSet<Hash256>,
+    max_per_slot: usize,
+}
+
+impl EarlyBlockQueue {
+    /// Instantiates a new, empty queue.
+    pub fn new(max_per_slot: usize) -> Self {
+        Self {
+            blocks: BTreeMap::new(),
+            roots: HashSet::new(),
+            max_per_slot,
+        }
+    }
+
+    /// Queue `block` (unless a block with `block_root` is already queued), returning `false` if
+    /// the slot of `block` is full and the block was not queued.
+    pub fn push(&mut self, block_root: Hash256, block: BeaconBlock) -> bool {
+        if self.roots.contains(&block_root) {
+            return true;
+        }
+
+        let slot_blocks = self.blocks.entry(block.slot).or_insert_with(Vec::new);
+        if slot_blocks.len() >= self.max_per_slot {
+            return false;
+        }
+        slot_blocks.push((block_root, block));
+        self.roots.insert(block_root);
+        true
+    }
+
+    /// Removes and returns all blocks with a slot at or before `present_slot`, ordered by slot
+    /// (blocks with the same slot are returned in the order they were queued).
+    pub fn pop_ready(&mut self, present_slot: Slot) -> Vec<BeaconBlock> {
+        let remaining = self.blocks.split_off(&(present_slot + 1));
+        let ready = std::mem::replace(&mut self.blocks, remaining);
+
+        let roots = &mut self.roots;
+        ready
+            .into_iter()
+            .flat_map(|(_, blocks)| blocks)
+            .map(|(block_root, block)| {
+                roots.remove(&block_root);
+                block
+            })
+            .collect()
+    }
+
+    /// Returns the number of queued blocks.
+    pub fn len(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Returns `true` if there are no queued blocks.
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::test_utils::{SeedableRng, TestRandom, XorShiftRng};
+
+    fn block(rng: &mut XorShiftRng, slot: u64) -> BeaconBlock {
+        let mut block = BeaconBlock::random_for_test(rng);
+        block.slot = Slot::new(slot);
+        block
+    }
+
+    fn push(queue: &mut EarlyBlockQueue, block: &BeaconBlock) -> bool {
+        queue.push(block.canonical_root(), block.clone())
+    }
+
+    #[test]
+    fn pop_ready() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut queue = EarlyBlockQueue::new(8);
+
+        let late = block(&mut rng, 3);
+        let early_a = block(&mut rng, 2);
+        let early_b = block(&mut rng, 2);
+
+        assert!(push(&mut queue, &late));
+        assert!(push(&mut queue, &early_a));
+        assert!(push(&mut queue, &early_b));
+
+        assert!(queue.pop_ready(Slot::new(1)).is_empty());
+        assert_eq!(queue.pop_ready(Slot::new(2)), vec![early_a, early_b]);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pop_ready(Slot::new(4)), vec![late]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn ignores_duplicates() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut queue = EarlyBlockQueue::new(8);
+
+        let early = block(&mut rng, 1);
+
+        assert!(push(&mut queue, &early));
+        assert!(push(&mut queue, &early));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.pop_ready(Slot::new(1)), vec![early.clone()]);
+
+        // Once popped, the block may be queued again.
+        assert!(push(&mut queue, &early));
+    }
+
+    #[test]
+    fn refuses_when_slot_is_full() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let mut queue = EarlyBlockQueue::new(1);
+
+        assert!(push(&mut queue, &block(&mut rng, 1)));
+        assert!(!push(&mut queue, &block(&mut rng, 1)));
+        assert!(push(&mut queue, &block(&mut rng, 2)));
+        assert_eq!(queue.len(), 2);
+    }
+}
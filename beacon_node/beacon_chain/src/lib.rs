@@ -1,7 +1,12 @@
 mod attestation_aggregator;
+mod attestation_reprocess_queue;
 mod beacon_chain;
 mod checkpoint;
+mod early_block_queue;
 
+pub use self::attestation_aggregator::{
+    Message as AggregationMessage, Outcome as AggregationOutcome,
+};
 pub use self::beacon_chain::{
     BeaconChain, BlockProcessingOutcome, Error, InvalidBlock, ValidBlock,
 };
@@ -122,11 +122,12 @@ impl BeaconChainHarness {
 
     /// Move the `slot_clock` for the `BeaconChain` forward one slot.
     ///
-    /// This is the equivalent of advancing a system clock forward one `SLOT_DURATION`.
+    /// This is the equivalent of advancing a system clock forward one `SLOT_DURATION`. Any blocks
+    /// which were queued because they arrived before the new slot are then processed.
     ///
     /// Returns the new slot.
     pub fn increment_beacon_chain_slot(&mut self) -> Slot {
-        let slot = self.beacon_chain.present_slot() + 1;
+        let slot = self.beacon_chain.slot_clock.advance_slot();
 
         let nth_slot = slot
             - slot
@@ -141,8 +142,12 @@ impl BeaconChainHarness {
             nth_slot
         );
 
-        self.beacon_chain.slot_clock.set_slot(slot.as_u64());
         self.beacon_chain.advance_state(slot).unwrap();
+
+        for outcome in self.beacon_chain.process_early_blocks().unwrap() {
+            debug!("Processed early block: {:?}", outcome);
+        }
+
         slot
     }
 
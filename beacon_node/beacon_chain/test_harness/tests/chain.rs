@@ -1,7 +1,19 @@
+use beacon_chain::{
+    AggregationMessage, BeaconChain, BlockProcessingOutcome, Error as BeaconChainError, ForkChoice,
+    InvalidBlock, ValidBlock,
+};
+use db::{
+    stores::{BeaconBlockStore, BeaconStateStore, OpPoolStore},
+    MemoryDB,
+};
 use env_logger::{Builder, Env};
+use fork_choice::BitwiseLMDGhost;
 use log::debug;
+use slot_clock::TestingSlotClock;
+use state_processing::{BlockProcessingError, BlockReplayer, SlotProcessable};
+use std::sync::Arc;
 use test_harness::BeaconChainHarness;
-use types::ChainSpec;
+use types::{BeaconState, ChainSpec, FreeAttestation, Hash256, Signature};
 
 #[test]
 fn it_can_build_on_genesis_block() {
@@ -44,3 +56,411 @@ fn it_can_produce_past_first_epoch_boundary() {
 
     harness.dump_to_file("/tmp/chaindump.json".to_string(), &dump);
 }
+
+#[test]
+fn it_can_replay_blocks_from_genesis() {
+    let spec = ChainSpec::few_validators();
+    let validator_count = 8;
+
+    let mut harness = BeaconChainHarness::new(spec, validator_count);
+
+    for _ in 0..3 {
+        harness.advance_chain_with_block();
+    }
+
+    let mut dump = harness.chain_dump().expect("Chain dump failed.");
+    dump.reverse(); // Genesis first.
+
+    let genesis = dump.remove(0);
+    let blocks: Vec<_> = dump.iter().map(|c| c.beacon_block.clone()).collect();
+
+    let mut hooked_slots = vec![];
+    let state = {
+        let mut replayer = BlockReplayer::new(genesis.beacon_state, &harness.spec)
+            .post_block_hook(|state, _block| hooked_slots.push(state.slot));
+        replayer.apply_blocks(&blocks).expect("Replay failed.");
+        replayer.into_state()
+    };
+
+    let head = dump.last().expect("Chain has a head.");
+    assert_eq!(state.canonical_root(), head.beacon_state_root);
+    assert_eq!(
+        hooked_slots,
+        blocks.iter().map(|block| block.slot).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn it_queues_blocks_which_arrive_before_their_slot() {
+    let spec = ChainSpec::few_validators();
+    let validator_count = 8;
+
+    let mut harness = BeaconChainHarness::new(spec, validator_count);
+
+    harness.advance_chain_with_block();
+
+    // Produce a block for the next slot, then wind the clock back so it arrives early.
+    let slot = harness.increment_beacon_chain_slot();
+    let block = harness.produce_block();
+    let block_root = block.canonical_root();
+    harness
+        .beacon_chain
+        .slot_clock
+        .set_slot((slot - 1).as_u64());
+
+    // Early blocks with an unknown parent or a bad signature are not queued.
+    let mut orphan = block.clone();
+    orphan.parent_root = Hash256::from(&[42; 32][..]);
+    assert_eq!(
+        harness.beacon_chain.process_block(orphan),
+        Ok(BlockProcessingOutcome::InvalidBlock(
+            InvalidBlock::ParentUnknown
+        ))
+    );
+    let mut unsigned = block.clone();
+    unsigned.signature = harness.spec.empty_signature.clone();
+    assert_eq!(
+        harness.beacon_chain.process_block(unsigned),
+        Ok(BlockProcessingOutcome::InvalidBlock(
+            InvalidBlock::PerBlockProcessingError(BlockProcessingError::BadBlockSignature)
+        ))
+    );
+
+    assert_eq!(
+        harness.beacon_chain.process_block(block.clone()),
+        Ok(BlockProcessingOutcome::Queued)
+    );
+    assert_ne!(harness.beacon_chain.head().beacon_block_root, block_root);
+
+    // The block is processed once its slot starts.
+    harness.increment_beacon_chain_slot();
+    assert_eq!(harness.beacon_chain.head().beacon_block_root, block_root);
+}
+
+#[test]
+fn it_can_start_from_an_exported_genesis_state() {
+    let spec = ChainSpec::few_validators();
+    let validator_count = 8;
+
+    let mut harness = BeaconChainHarness::new(spec, validator_count);
+    harness.advance_chain_with_block();
+
+    let from_state = |state: BeaconState| {
+        let db = Arc::new(MemoryDB::open());
+        let block_store = Arc::new(BeaconBlockStore::new(db.clone()));
+        let state_store = Arc::new(BeaconStateStore::new(db.clone()));
+        let fork_choice = BitwiseLMDGhost::new(block_store.clone(), state_store.clone());
+        BeaconChain::from_genesis_state(
+            state_store,
+            block_store,
+            TestingSlotClock::new(harness.spec.genesis_slot.as_u64()),
+            state,
+            (*harness.spec).clone(),
+            fork_choice,
+        )
+    };
+
+    let genesis_state = harness.beacon_chain.genesis_state().unwrap();
+    let chain = from_state(genesis_state).unwrap();
+    assert_eq!(
+        chain.genesis_block_root,
+        harness.beacon_chain.genesis_block_root
+    );
+
+    // A state beyond genesis is refused.
+    let head_state = harness.beacon_chain.head().beacon_state.clone();
+    let head_slot = head_state.slot;
+    assert_eq!(
+        from_state(head_state).err(),
+        Some(BeaconChainError::NotGenesisState(head_slot))
+    );
+}
+
+#[test]
+fn it_verifies_the_wss_checkpoint() {
+    let spec = ChainSpec::few_validators();
+    let validator_count = 8;
+
+    let harness = BeaconChainHarness::new(spec, validator_count);
+    let chain = &harness.beacon_chain;
+    let genesis_epoch = harness.spec.genesis_epoch;
+    let wrong_root = Hash256::from(&[42; 32][..]);
+
+    assert_eq!(
+        chain.set_wss_checkpoint(chain.genesis_block_root, genesis_epoch),
+        Ok(true)
+    );
+    assert_eq!(
+        chain.set_wss_checkpoint(wrong_root, genesis_epoch),
+        Err(BeaconChainError::WeakSubjectivityConflict {
+            epoch: genesis_epoch,
+            canonical_root: chain.genesis_block_root,
+        })
+    );
+    assert_eq!(
+        chain.set_wss_checkpoint(wrong_root, genesis_epoch - 1),
+        Err(BeaconChainError::EpochBeforeGenesis(genesis_epoch - 1))
+    );
+
+    // The head has not reached later epochs, so they are verified as blocks arrive.
+    assert_eq!(
+        chain.set_wss_checkpoint(wrong_root, genesis_epoch + 1),
+        Ok(false)
+    );
+}
+
+#[test]
+fn it_rejects_blocks_which_conflict_with_the_wss_checkpoint() {
+    let spec = ChainSpec::few_validators();
+    let validator_count = 8;
+
+    let mut harness = BeaconChainHarness::new(spec, validator_count);
+    let chain = harness.beacon_chain.clone();
+    let checkpoint_epoch = harness.spec.genesis_epoch + 1;
+
+    // Skip to the first slot of the checkpoint epoch and produce a block upon genesis.
+    for _ in 0..harness.spec.epoch_length {
+        harness.increment_beacon_chain_slot();
+    }
+    let block = harness.produce_block();
+    let block_root = block.canonical_root();
+    assert_eq!(
+        block.slot,
+        checkpoint_epoch.start_slot(harness.spec.epoch_length)
+    );
+
+    // A checkpoint from some other chain.
+    let other_root = Hash256::from(&[42; 32][..]);
+    assert_eq!(
+        chain.set_wss_checkpoint(other_root, checkpoint_epoch),
+        Ok(false)
+    );
+    assert_eq!(
+        chain.process_block(block.clone()),
+        Ok(BlockProcessingOutcome::InvalidBlock(
+            InvalidBlock::WeakSubjectivityConflict
+        ))
+    );
+    assert_eq!(chain.head().beacon_block_root, chain.genesis_block_root);
+
+    // A checkpoint on the chain of the block.
+    assert_eq!(
+        chain.set_wss_checkpoint(block_root, checkpoint_epoch),
+        Ok(false)
+    );
+    assert_eq!(
+        chain.process_block(block),
+        Ok(BlockProcessingOutcome::ValidBlock(ValidBlock::Processed))
+    );
+    assert_eq!(chain.head().beacon_block_root, block_root);
+
+    // Now the head has reached the checkpoint it is verified immediately.
+    assert_eq!(
+        chain.set_wss_checkpoint(block_root, checkpoint_epoch),
+        Ok(true)
+    );
+}
+
+#[test]
+fn it_restores_only_valid_attestations() {
+    let spec = ChainSpec::few_validators();
+    let validator_count = 8;
+
+    let mut harness = BeaconChainHarness::new(spec, validator_count);
+    harness.advance_chain_with_block();
+
+    let attestations = harness
+        .beacon_chain
+        .attestation_aggregator
+        .read()
+        .attestations();
+    assert!(!attestations.is_empty());
+
+    // An attestation whose data no longer matches its signature.
+    let mut tampered = attestations[0].clone();
+    tampered.data.justified_block_root = Hash256::from(&[42; 32][..]);
+
+    let mut persisted = attestations.clone();
+    persisted.push(tampered);
+
+    // Restore into a second chain with the same genesis (e.g., after a restart).
+    let db = Arc::new(MemoryDB::open());
+    let block_store = Arc::new(BeaconBlockStore::new(db.clone()));
+    let state_store = Arc::new(BeaconStateStore::new(db.clone()));
+    let op_pool_store = OpPoolStore::new(db.clone());
+    let fork_choice = BitwiseLMDGhost::new(block_store.clone(), state_store.clone());
+    let chain = BeaconChain::from_genesis_state(
+        state_store,
+        block_store,
+        TestingSlotClock::new(harness.spec.genesis_slot.as_u64()),
+        harness.beacon_chain.genesis_state().unwrap(),
+        (*harness.spec).clone(),
+        fork_choice,
+    )
+    .unwrap();
+
+    op_pool_store
+        .put_attestations(&chain.genesis_block_root, &persisted)
+        .unwrap();
+    assert_eq!(
+        chain.restore_op_pool(&op_pool_store),
+        Ok(attestations.len())
+    );
+
+    // Once the slot clock has moved on, the same attestations are too old to be restored.
+    let late_slot = harness.spec.genesis_slot + harness.spec.epoch_length * 3;
+    chain.slot_clock.set_slot(late_slot.as_u64());
+    *chain.attestation_aggregator.write() = Default::default();
+    assert_eq!(chain.restore_op_pool(&op_pool_store), Ok(0));
+}
+
+#[test]
+fn it_records_reorgs() {
+    let spec = ChainSpec::few_validators();
+    let validator_count = 8;
+
+    let mut harness = BeaconChainHarness::new(spec, validator_count);
+    let chain = harness.beacon_chain.clone();
+    let spec = harness.spec.clone();
+    let genesis = chain.finalized_head().clone();
+    let genesis_root = chain.genesis_block_root;
+    // Unlike the state of the genesis checkpoint, the present state has its caches built.
+    let genesis_state = chain.state.read().clone();
+
+    // Point the latest vote of every validator at `root`.
+    let vote_for = |root: Hash256| {
+        for validator_index in 0..validator_count as u64 {
+            chain
+                .fork_choice
+                .write()
+                .add_attestation(validator_index, &root, &spec)
+                .unwrap();
+        }
+    };
+
+    // Block `a` builds upon genesis and becomes the head.
+    harness.increment_beacon_chain_slot();
+    let block_a = harness.produce_block();
+    let root_a = block_a.canonical_root();
+    assert_eq!(
+        chain.process_block(block_a),
+        Ok(BlockProcessingOutcome::ValidBlock(ValidBlock::Processed))
+    );
+
+    // Moving the head from genesis to its descendant `a` is not a reorg.
+    chain.update_canonical_head(
+        genesis.beacon_block.clone(),
+        genesis.beacon_block_root,
+        genesis.beacon_state.clone(),
+        genesis.beacon_state_root,
+    );
+    vote_for(root_a);
+    chain.fork_choice().unwrap();
+    assert_eq!(chain.head().beacon_block_root, root_a);
+    assert!(chain.reorgs_since(spec.genesis_epoch).is_empty());
+
+    // Block `b` skips the slot of `a` and also builds upon genesis.
+    harness.increment_beacon_chain_slot();
+    let mut state = genesis_state;
+    for _ in 0..2 {
+        state.per_slot_processing(genesis_root, &spec).unwrap();
+    }
+    *chain.state.write() = state;
+    let block_b = harness.produce_block();
+    let root_b = block_b.canonical_root();
+    let slot_b = block_b.slot;
+    assert_eq!(block_b.parent_root, genesis_root);
+    assert_eq!(
+        chain.process_block(block_b),
+        Ok(BlockProcessingOutcome::ValidBlock(ValidBlock::Processed))
+    );
+    assert_eq!(chain.head().beacon_block_root, root_a);
+
+    // Once the votes move to `b`, `a` is orphaned.
+    vote_for(root_b);
+    chain.fork_choice().unwrap();
+    assert_eq!(chain.head().beacon_block_root, root_b);
+
+    let reorgs = chain.reorgs_since(spec.genesis_epoch);
+    assert_eq!(reorgs.len(), 1);
+    let reorg = &reorgs[0];
+    assert_eq!(reorg.slot, slot_b);
+    assert_eq!(reorg.old_head_root, root_a);
+    assert_eq!(reorg.new_head_root, root_b);
+    assert_eq!(reorg.common_ancestor_root, genesis_root);
+    assert_eq!(reorg.orphaned_block_roots, vec![root_a]);
+    assert_eq!(reorg.depth(), 1);
+
+    assert!(chain.reorgs_since(spec.genesis_epoch + 1).is_empty());
+}
+
+#[test]
+fn it_reprocesses_attestations_once_their_block_is_imported() {
+    let spec = ChainSpec::few_validators();
+    let validator_count = 8;
+
+    let mut harness = BeaconChainHarness::new(spec, validator_count);
+    let chain = harness.beacon_chain.clone();
+
+    let slot = harness.increment_beacon_chain_slot();
+    let block = harness.produce_block();
+    let block_root = block.canonical_root();
+
+    // An attestation to `block`, from some validator which attests at this slot.
+    let (validator_index, shard) = (0..validator_count)
+        .filter_map(
+            |i| match chain.validator_attestion_slot_and_shard(i).unwrap() {
+                Some((attestation_slot, shard)) if attestation_slot == slot => Some((i, shard)),
+                _ => None,
+            },
+        )
+        .next()
+        .expect("No validator attests at this slot.");
+    let mut data = chain.produce_attestation_data(shard).unwrap();
+    data.beacon_block_root = block_root;
+    let signature = Signature::new(
+        &data.signable_message(false),
+        harness.spec.domain_attestation,
+        &harness.validators[validator_index].keypair.sk,
+    );
+    let free_attestation = FreeAttestation {
+        data,
+        signature,
+        validator_index: validator_index as u64,
+    };
+
+    // The block is not yet imported, so the attestation is queued.
+    let outcome = chain.process_free_attestation(free_attestation).unwrap();
+    assert!(!outcome.valid);
+    match outcome.message {
+        AggregationMessage::UnknownBeaconBlock => {}
+        _ => panic!("Attestation was not queued."),
+    }
+    assert!(chain
+        .attestation_aggregator
+        .read()
+        .attestations()
+        .is_empty());
+
+    // Importing the block processes the queued attestation.
+    assert_eq!(
+        chain.process_block(block),
+        Ok(BlockProcessingOutcome::ValidBlock(ValidBlock::Processed))
+    );
+
+    let attestations = chain.attestation_aggregator.read().attestations();
+    assert_eq!(attestations.len(), 1);
+    assert_eq!(attestations[0].data.beacon_block_root, block_root);
+
+    let genesis = chain.finalized_head().clone();
+    let votes = chain
+        .fork_choice
+        .read()
+        .get_latest_votes(
+            &genesis.beacon_state_root,
+            genesis.beacon_block.slot,
+            &harness.spec,
+        )
+        .unwrap();
+    assert!(votes.contains_key(&block_root));
+}
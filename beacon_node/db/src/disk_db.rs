@@ -28,6 +28,7 @@ impl DiskDB {
          */
         let mut options = Options::default();
         options.create_if_missing(true);
+        options.create_missing_column_families(true);
 
         // TODO: ensure that columns are created (and remove
         // the dead_code allow)
@@ -8,6 +8,8 @@ use types::{readers::BeaconBlockReader, BeaconBlock, Hash256, Slot};
 pub enum BeaconBlockAtSlotError {
     UnknownBeaconBlock(Hash256),
     InvalidBeaconBlock(Hash256),
+    /// The slot is prior to the genesis block (i.e., the block with a zero `parent_root`).
+    SlotBeforeGenesis(Slot),
     DBError(String),
 }
 
@@ -89,6 +91,34 @@ impl<T: ClientDB> BeaconBlockStore<T> {
             }
         }
     }
+
+    /// Retrieve the hash of the latest block with a slot less than or equal to `slot`, given a
+    /// "head_hash".
+    ///
+    /// Unlike `block_at_slot`, a skipped slot resolves to the most recent block prior to it, which
+    /// is the block referred to by an epoch boundary checkpoint. If `slot` is not less than the
+    /// slot of the "head_hash" block, "head_hash" is returned.
+    pub fn block_root_at_or_before_slot(
+        &self,
+        head_hash: &Hash256,
+        slot: Slot,
+    ) -> Result<Hash256, BeaconBlockAtSlotError> {
+        let mut current_hash = *head_hash;
+
+        loop {
+            if let Some(block_reader) = self.get_reader(&current_hash)? {
+                if block_reader.slot() <= slot {
+                    break Ok(current_hash);
+                } else if block_reader.parent_root() == Hash256::zero() {
+                    break Err(BeaconBlockAtSlotError::SlotBeforeGenesis(slot));
+                } else {
+                    current_hash = block_reader.parent_root();
+                }
+            } else {
+                break Err(BeaconBlockAtSlotError::UnknownBeaconBlock(current_hash));
+            }
+        }
+    }
 }
 
 impl From<DBError> for BeaconBlockAtSlotError {
@@ -200,6 +230,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_block_root_before_genesis() {
+        let db = Arc::new(MemoryDB::open());
+        let bs = BeaconBlockStore::new(db.clone());
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+
+        let mut genesis = BeaconBlock::random_for_test(&mut rng);
+        genesis.parent_root = Hash256::zero();
+        genesis.slot = Slot::new(10);
+        let genesis_hash = Hash256::from(&[1; 32][..]);
+        db.put(DB_COLUMN, &genesis_hash, &ssz_encode(&genesis))
+            .unwrap();
+
+        assert_eq!(
+            bs.block_root_at_or_before_slot(&genesis_hash, Slot::new(10)),
+            Ok(genesis_hash)
+        );
+        assert_eq!(
+            bs.block_root_at_or_before_slot(&genesis_hash, Slot::new(9)),
+            Err(BeaconBlockAtSlotError::SlotBeforeGenesis(Slot::new(9)))
+        );
+    }
+
     #[test]
     fn test_block_at_slot() {
         let db = Arc::new(MemoryDB::open());
@@ -252,6 +305,15 @@ mod tests {
         let ssz = bs.block_at_slot(&hashes[4], Slot::new(2)).unwrap();
         assert_eq!(ssz, None);
 
+        // Skipped slots resolve to the prior block, future slots to the head.
+        let test_cases = vec![(0, 0), (1, 1), (2, 1), (3, 2), (5, 4), (6, 4)];
+        for (slot, hashes_index) in test_cases {
+            let root = bs
+                .block_root_at_or_before_slot(&hashes[4], Slot::new(slot))
+                .unwrap();
+            assert_eq!(root, hashes[hashes_index]);
+        }
+
         let ssz = bs.block_at_slot(&hashes[4], Slot::new(6)).unwrap();
         assert_eq!(ssz, None);
 
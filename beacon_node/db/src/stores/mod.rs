@@ -4,22 +4,30 @@ use super::{ClientDB, DBError};
 mod macros;
 mod beacon_block_store;
 mod beacon_state_store;
+mod op_pool_store;
 mod pow_chain_store;
+mod reorg_store;
 mod validator_store;
 
 pub use self::beacon_block_store::{BeaconBlockAtSlotError, BeaconBlockStore};
 pub use self::beacon_state_store::BeaconStateStore;
+pub use self::op_pool_store::OpPoolStore;
 pub use self::pow_chain_store::PoWChainStore;
+pub use self::reorg_store::{ReorgEvent, ReorgStore};
 pub use self::validator_store::{ValidatorStore, ValidatorStoreError};
 
 pub const BLOCKS_DB_COLUMN: &str = "blocks";
 pub const STATES_DB_COLUMN: &str = "states";
 pub const POW_CHAIN_DB_COLUMN: &str = "powchain";
 pub const VALIDATOR_DB_COLUMN: &str = "validator";
+pub const OP_POOL_DB_COLUMN: &str = "oppool";
+pub const REORG_DB_COLUMN: &str = "reorgs";
 
-pub const COLUMNS: [&str; 4] = [
+pub const COLUMNS: [&str; 6] = [
     BLOCKS_DB_COLUMN,
     STATES_DB_COLUMN,
     POW_CHAIN_DB_COLUMN,
     VALIDATOR_DB_COLUMN,
+    OP_POOL_DB_COLUMN,
+    REORG_DB_COLUMN,
 ];
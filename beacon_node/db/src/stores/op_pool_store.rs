@@ -0,0 +1,111 @@
+use super::OP_POOL_DB_COLUMN as DB_COLUMN;
+use super::{ClientDB, DBError};
+use ssz::{ssz_encode, Decodable};
+use std::sync::Arc;
+use types::{Attestation, Hash256};
+
+/// Persists the operations which are awaiting inclusion in a block (the "op pool") so they are
+/// not lost when the node restarts.
+///
+/// The attestations are stored as a single SSZ list, keyed by the genesis block root of the chain
+/// they belong to, so attestations from one chain are never restored into another.
+pub struct OpPoolStore<T>
+where
+    T: ClientDB,
+{
+    db: Arc<T>,
+}
+
+impl<T: ClientDB> OpPoolStore<T> {
+    pub fn new(db: Arc<T>) -> Self {
+        Self { db }
+    }
+
+    /// Replaces any previously persisted attestations for the given chain with `attestations`.
+    pub fn put_attestations(
+        &self,
+        genesis_block_root: &Hash256,
+        attestations: &[Attestation],
+    ) -> Result<(), DBError> {
+        self.db.put(
+            DB_COLUMN,
+            genesis_block_root,
+            &ssz_encode(&attestations.to_vec())[..],
+        )
+    }
+
+    /// Returns the persisted attestations for the given chain, or an empty list if none have been
+    /// persisted.
+    pub fn get_attestations(
+        &self,
+        genesis_block_root: &Hash256,
+    ) -> Result<Vec<Attestation>, DBError> {
+        match self.db.get(DB_COLUMN, genesis_block_root)? {
+            None => Ok(vec![]),
+            Some(ssz) => {
+                let (attestations, _) =
+                    Vec::<Attestation>::ssz_decode(&ssz, 0).map_err(|_| DBError {
+                        message: "Bad Attestation SSZ.".to_string(),
+                    })?;
+                Ok(attestations)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::MemoryDB;
+    use super::*;
+    use types::test_utils::{SeedableRng, TestRandom, XorShiftRng};
+
+    #[test]
+    fn test_attestations_round_trip() {
+        let db = Arc::new(MemoryDB::open());
+        let store = OpPoolStore::new(db.clone());
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let genesis = Hash256::from(&[1; 32][..]);
+        let other_genesis = Hash256::from(&[2; 32][..]);
+
+        let attestations: Vec<Attestation> = (0..3)
+            .map(|_| Attestation::random_for_test(&mut rng))
+            .collect();
+
+        store.put_attestations(&genesis, &attestations).unwrap();
+        assert_eq!(store.get_attestations(&genesis).unwrap(), attestations);
+
+        // Attestations are not shared between chains.
+        assert!(store.get_attestations(&other_genesis).unwrap().is_empty());
+
+        // A second put replaces, rather than extends, the stored list.
+        store
+            .put_attestations(&genesis, &attestations[0..1])
+            .unwrap();
+        assert_eq!(
+            store.get_attestations(&genesis).unwrap(),
+            attestations[0..1].to_vec()
+        );
+    }
+
+    #[test]
+    fn test_get_attestations_when_empty() {
+        let db = Arc::new(MemoryDB::open());
+        let store = OpPoolStore::new(db.clone());
+
+        assert!(store
+            .get_attestations(&Hash256::from(&[1; 32][..]))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_get_invalid_attestations() {
+        let db = Arc::new(MemoryDB::open());
+        let store = OpPoolStore::new(db.clone());
+
+        let genesis = Hash256::from(&[1; 32][..]);
+
+        db.put(DB_COLUMN, &genesis, &[1, 2, 3]).unwrap();
+        assert!(store.get_attestations(&genesis).is_err());
+    }
+}
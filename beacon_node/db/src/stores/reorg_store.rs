@@ -0,0 +1,169 @@
+use super::REORG_DB_COLUMN as DB_COLUMN;
+use super::{ClientDB, DBError};
+use ssz::{ssz_encode, Decodable, DecodeError, Encodable, SszStream};
+use std::sync::Arc;
+use types::{Hash256, Slot};
+
+/// Describes a change of the canonical head to a block which does not descend from the previous
+/// head.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ReorgEvent {
+    /// The slot of the new head block.
+    pub slot: Slot,
+    pub old_head_root: Hash256,
+    pub new_head_root: Hash256,
+    /// The most recent block which is an ancestor of both the old and new heads.
+    pub common_ancestor_root: Hash256,
+    /// The roots of the blocks which were canonical prior to the reorg but are not anymore,
+    /// starting with the old head.
+    pub orphaned_block_roots: Vec<Hash256>,
+    /// The time at which the reorg occurred, in seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+impl ReorgEvent {
+    /// Returns the number of blocks which were removed from the canonical chain.
+    pub fn depth(&self) -> usize {
+        self.orphaned_block_roots.len()
+    }
+}
+
+impl Encodable for ReorgEvent {
+    fn ssz_append(&self, s: &mut SszStream) {
+        s.append(&self.slot);
+        s.append(&self.old_head_root);
+        s.append(&self.new_head_root);
+        s.append(&self.common_ancestor_root);
+        s.append(&self.orphaned_block_roots);
+        s.append(&self.timestamp);
+    }
+}
+
+impl Decodable for ReorgEvent {
+    fn ssz_decode(bytes: &[u8], i: usize) -> Result<(Self, usize), DecodeError> {
+        let (slot, i) = <_>::ssz_decode(bytes, i)?;
+        let (old_head_root, i) = <_>::ssz_decode(bytes, i)?;
+        let (new_head_root, i) = <_>::ssz_decode(bytes, i)?;
+        let (common_ancestor_root, i) = <_>::ssz_decode(bytes, i)?;
+        let (orphaned_block_roots, i) = <_>::ssz_decode(bytes, i)?;
+        let (timestamp, i) = <_>::ssz_decode(bytes, i)?;
+
+        Ok((
+            Self {
+                slot,
+                old_head_root,
+                new_head_root,
+                common_ancestor_root,
+                orphaned_block_roots,
+                timestamp,
+            },
+            i,
+        ))
+    }
+}
+
+/// Persists a record of recent reorgs so they may be analysed after the fact (e.g., after the
+/// node restarts).
+///
+/// The events are stored as a single SSZ list, keyed by the genesis block root of the chain they
+/// occurred on.
+pub struct ReorgStore<T>
+where
+    T: ClientDB,
+{
+    db: Arc<T>,
+}
+
+impl<T: ClientDB> ReorgStore<T> {
+    pub fn new(db: Arc<T>) -> Self {
+        Self { db }
+    }
+
+    /// Replaces any previously persisted reorg events for the given chain with `reorgs`.
+    pub fn put_reorgs(
+        &self,
+        genesis_block_root: &Hash256,
+        reorgs: &[ReorgEvent],
+    ) -> Result<(), DBError> {
+        self.db.put(
+            DB_COLUMN,
+            genesis_block_root,
+            &ssz_encode(&reorgs.to_vec())[..],
+        )
+    }
+
+    /// Returns the persisted reorg events for the given chain, or an empty list if none have been
+    /// persisted.
+    pub fn get_reorgs(&self, genesis_block_root: &Hash256) -> Result<Vec<ReorgEvent>, DBError> {
+        match self.db.get(DB_COLUMN, genesis_block_root)? {
+            None => Ok(vec![]),
+            Some(ssz) => {
+                let (reorgs, _) = Vec::<ReorgEvent>::ssz_decode(&ssz, 0).map_err(|_| DBError {
+                    message: "Bad ReorgEvent SSZ.".to_string(),
+                })?;
+                Ok(reorgs)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::MemoryDB;
+    use super::*;
+
+    fn reorg_event(n: u8) -> ReorgEvent {
+        ReorgEvent {
+            slot: Slot::new(u64::from(n)),
+            old_head_root: Hash256::from(&[n; 32][..]),
+            new_head_root: Hash256::from(&[n + 1; 32][..]),
+            common_ancestor_root: Hash256::from(&[n + 2; 32][..]),
+            orphaned_block_roots: vec![Hash256::from(&[n; 32][..]); usize::from(n)],
+            timestamp: 1_549_935_547 + u64::from(n),
+        }
+    }
+
+    #[test]
+    fn test_reorgs_round_trip() {
+        let db = Arc::new(MemoryDB::open());
+        let store = ReorgStore::new(db.clone());
+
+        let genesis = Hash256::from(&[1; 32][..]);
+        let other_genesis = Hash256::from(&[2; 32][..]);
+
+        let reorgs: Vec<ReorgEvent> = (0..3).map(reorg_event).collect();
+
+        store.put_reorgs(&genesis, &reorgs).unwrap();
+        assert_eq!(store.get_reorgs(&genesis).unwrap(), reorgs);
+        assert_eq!(reorgs[2].depth(), 2);
+
+        // Reorgs are not shared between chains.
+        assert!(store.get_reorgs(&other_genesis).unwrap().is_empty());
+
+        // A second put replaces, rather than extends, the stored list.
+        store.put_reorgs(&genesis, &reorgs[0..1]).unwrap();
+        assert_eq!(store.get_reorgs(&genesis).unwrap(), reorgs[0..1].to_vec());
+    }
+
+    #[test]
+    fn test_get_reorgs_when_empty() {
+        let db = Arc::new(MemoryDB::open());
+        let store = ReorgStore::new(db.clone());
+
+        assert!(store
+            .get_reorgs(&Hash256::from(&[1; 32][..]))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_get_invalid_reorgs() {
+        let db = Arc::new(MemoryDB::open());
+        let store = ReorgStore::new(db.clone());
+
+        let genesis = Hash256::from(&[1; 32][..]);
+
+        db.put(DB_COLUMN, &genesis, &[1, 2, 3]).unwrap();
+        assert!(store.get_reorgs(&genesis).is_err());
+    }
+}
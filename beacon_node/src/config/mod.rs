@@ -1,5 +1,6 @@
 use std::fs;
 use std::path::PathBuf;
+use types::{Epoch, Hash256};
 
 /// Stores the core configuration for this Lighthouse instance.
 /// This struct is general, other components may implement more
@@ -8,6 +9,8 @@ use std::path::PathBuf;
 pub struct LighthouseConfig {
     pub data_dir: PathBuf,
     pub p2p_listen_port: u16,
+    /// A block root and epoch which the canonical chain must include.
+    pub wss_checkpoint: Option<(Hash256, Epoch)>,
 }
 
 const DEFAULT_LIGHTHOUSE_DIR: &str = ".lighthouse";
@@ -25,6 +28,7 @@ impl LighthouseConfig {
         Self {
             data_dir,
             p2p_listen_port,
+            wss_checkpoint: None,
         }
     }
 }
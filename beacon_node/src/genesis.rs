@@ -0,0 +1,51 @@
+use ssz::{ssz_encode, Decodable};
+use std::fs;
+use std::path::Path;
+use types::BeaconState;
+
+/// Writes `state` to the file at `path` as SSZ, so it may be loaded by `read_genesis_state`.
+pub fn write_genesis_state(path: &Path, state: &BeaconState) -> Result<(), String> {
+    fs::write(path, ssz_encode(state))
+        .map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+}
+
+/// Reads an SSZ-encoded `BeaconState` from the file at `path`.
+pub fn read_genesis_state(path: &Path) -> Result<BeaconState, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Unable to read {}: {}", path.display(), e))?;
+
+    let (state, i) = BeaconState::ssz_decode(&bytes, 0)
+        .map_err(|e| format!("Invalid genesis state SSZ: {:?}", e))?;
+    if i != bytes.len() {
+        return Err(format!(
+            "Invalid genesis state SSZ: {} trailing bytes",
+            bytes.len() - i
+        ));
+    }
+
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use types::test_utils::{SeedableRng, TestRandom, XorShiftRng};
+
+    #[test]
+    fn genesis_state_round_trip() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let state = BeaconState::random_for_test(&mut rng);
+        let path = env::temp_dir().join("lighthouse_genesis_state_round_trip.ssz");
+
+        write_genesis_state(&path, &state).unwrap();
+        assert_eq!(read_genesis_state(&path), Ok(state));
+
+        // Trailing bytes indicate the file is not a single state.
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.push(0);
+        fs::write(&path, bytes).unwrap();
+        assert!(read_genesis_state(&path).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+use slog::{Level, Record};
+use std::str::FromStr;
+
+/// The minimum level of log records to be emitted, optionally overridden for individual modules.
+///
+/// Parsed from a comma-separated list such as `info,rpc=debug,rpc::validator=trace`. An entry
+/// without a module sets the default level (`info` if omitted). An entry with a module sets the
+/// level for that module and its submodules; the module may be given with or without the leading
+/// crate name. Where several entries match a module, the longest wins.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LogLevels {
+    default: Level,
+    modules: Vec<(String, Level)>,
+}
+
+impl LogLevels {
+    /// Instantiates a new `LogLevels`, applying `default` to all modules.
+    pub fn new(default: Level) -> Self {
+        Self {
+            default,
+            modules: vec![],
+        }
+    }
+
+    /// Returns the minimum level for records from `module` (as given by `module_path!()`).
+    pub fn level_for(&self, module: &str) -> Level {
+        let without_crate = module.splitn(2, "::").nth(1);
+
+        self.modules
+            .iter()
+            .filter(|(prefix, _)| {
+                is_within(module, prefix) || without_crate.map_or(false, |m| is_within(m, prefix))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    /// Returns the module of each directive whose first path segment is not one of `known`.
+    ///
+    /// Only records logged through `slog` are filtered, so a directive for a crate which logs
+    /// through the `log` crate (e.g., `beacon_chain`) has no effect.
+    pub fn unknown_modules(&self, known: &[&str]) -> Vec<&str> {
+        self.modules
+            .iter()
+            .map(|(module, _)| module.as_str())
+            .filter(|module| {
+                let first = module.split("::").next().unwrap_or(module);
+                !known.contains(&first)
+            })
+            .collect()
+    }
+
+    /// Returns `true` if `record` should be emitted.
+    pub fn accepts(&self, record: &Record) -> bool {
+        record.level().is_at_least(self.level_for(record.module()))
+    }
+}
+
+impl FromStr for LogLevels {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut levels = LogLevels::new(Level::Info);
+
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let mut parts = directive.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(module), Some(level)) => levels
+                    .modules
+                    .push((module.trim().to_string(), parse_level(level)?)),
+                (Some(level), None) => levels.default = parse_level(level)?,
+                (None, _) => unreachable!("splitn always yields at least one item"),
+            }
+        }
+
+        Ok(levels)
+    }
+}
+
+/// Returns `true` if `module` is `prefix` or one of its submodules.
+fn is_within(module: &str, prefix: &str) -> bool {
+    module == prefix || (module.starts_with(prefix) && module[prefix.len()..].starts_with("::"))
+}
+
+fn parse_level(level: &str) -> Result<Level, String> {
+    Level::from_str(level.trim()).map_err(|_| format!("Unknown log level: {}", level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_match_modules() {
+        let levels: LogLevels = "warn,rpc=debug,beacon_node::rpc::validator=trace"
+            .parse()
+            .unwrap();
+
+        assert_eq!(levels.level_for("beacon_node"), Level::Warning);
+        assert_eq!(levels.level_for("beacon_node::rpc"), Level::Debug);
+        assert_eq!(
+            levels.level_for("beacon_node::rpc::beacon_block"),
+            Level::Debug
+        );
+        assert_eq!(
+            levels.level_for("beacon_node::rpc::validator"),
+            Level::Trace
+        );
+        // `rpc` does not match modules which merely share the prefix.
+        assert_eq!(levels.level_for("beacon_node::rpcs"), Level::Warning);
+    }
+
+    #[test]
+    fn parse_defaults_to_info() {
+        let levels: LogLevels = "rpc=debug".parse().unwrap();
+        assert_eq!(levels.level_for("beacon_node"), Level::Info);
+        assert_eq!("".parse::<LogLevels>(), Ok(LogLevels::new(Level::Info)));
+    }
+
+    #[test]
+    fn unknown_modules() {
+        let levels: LogLevels = "warn,rpc=debug,beacon_node::rpc=trace,beacon_chain=info"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            levels.unknown_modules(&["beacon_node", "rpc"]),
+            vec!["beacon_chain"]
+        );
+    }
+
+    #[test]
+    fn parse_invalid_level() {
+        assert!("loud".parse::<LogLevels>().is_err());
+        assert!("rpc=loud".parse::<LogLevels>().is_err());
+    }
+}
@@ -1,31 +1,42 @@
 extern crate slog;
 
 mod config;
+mod genesis;
+mod logging;
 mod rpc;
 
 use std::path::PathBuf;
 
 use crate::config::LighthouseConfig;
+use crate::genesis::{read_genesis_state, write_genesis_state};
+use crate::logging::LogLevels;
 use crate::rpc::start_server;
-use beacon_chain::BeaconChain;
+use beacon_chain::{BeaconChain, Error as BeaconChainError};
 use bls::create_proof_of_possession;
-use clap::{App, Arg};
+use clap::{App, Arg, ArgMatches};
 use db::{
-    stores::{BeaconBlockStore, BeaconStateStore},
-    MemoryDB,
+    stores::{BeaconBlockStore, BeaconStateStore, OpPoolStore, ReorgStore, COLUMNS},
+    ClientDB, DiskDB, MemoryDB,
 };
 use fork_choice::BitwiseLMDGhost;
-use slog::{error, info, o, Drain};
+use slog::{error, info, o, warn, Drain, Level, Logger};
 use slot_clock::SystemTimeSlotClock;
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::sync::Arc;
-use types::{ChainSpec, Deposit, DepositData, DepositInput, Eth1Data, Hash256, Keypair};
+use std::time::Duration;
+use types::{
+    BeaconState, ChainSpec, Deposit, DepositData, DepositInput, Epoch, Eth1Data, Hash256, Keypair,
+};
 
-fn main() {
-    let decorator = slog_term::TermDecorator::new().build();
-    let drain = slog_term::CompactFormat::new(decorator).build().fuse();
-    let drain = slog_async::Async::new(drain).build().fuse();
-    let log = slog::Logger::root(drain, o!());
+/// How often the op pool and reorg history are written to the database, so they survive a
+/// restart. They are also written on shutdown.
+const PERSIST_INTERVAL_SECS: u64 = 60;
 
+/// The modules which log through `slog` and hence may be filtered by `--debug-level`.
+const SLOG_MODULES: &[&str] = &["beacon_node", "config", "genesis", "logging", "rpc"];
+
+fn main() {
     let matches = App::new("Lighthouse")
         .version("0.0.1")
         .author("Sigma Prime <paul@sigmaprime.io>")
@@ -44,8 +55,82 @@ fn main() {
                 .help("Network listen port for p2p connections.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("wss-checkpoint")
+                .long("wss-checkpoint")
+                .value_name("ROOT:EPOCH")
+                .help(
+                    "Refuse to run if the canonical chain does not include the block ROOT \
+                     (hex) at EPOCH, and reject any block on a chain without it. EPOCH must not \
+                     be before the genesis epoch.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("ignore-weak-subjectivity")
+                .long("ignore-weak-subjectivity")
+                .help(
+                    "Run even if the finalized state of an imported genesis is older than the \
+                     weak subjectivity period. Such a chain may have been rewritten by validators \
+                     that have since exited.",
+                ),
+        )
+        .arg(
+            Arg::with_name("export-genesis")
+                .long("export-genesis")
+                .value_name("FILE")
+                .help("Write the genesis state to FILE (as SSZ) so other nodes may start from it.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("import-genesis")
+                .long("import-genesis")
+                .value_name("FILE")
+                .help(
+                    "Start from the genesis state in FILE, as written by --export-genesis, \
+                     instead of generating a new one. Only a chain from an imported genesis is \
+                     stored in the data directory, so its op pool and reorg history survive a \
+                     restart.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("debug-level")
+                .long("debug-level")
+                .value_name("LEVELS")
+                .help(
+                    "Minimum log level, optionally per module. E.g., `info,rpc=debug` logs the \
+                     `rpc` module at `debug` and all others at `info`. Modules must be within \
+                     `beacon_node`; other crates (e.g., `beacon_chain`) are not filtered.",
+                )
+                .takes_value(true),
+        )
         .get_matches();
 
+    // Log levels (all levels are emitted unless specified).
+    let log_levels = match matches.value_of("debug-level").map(str::parse::<LogLevels>) {
+        None => LogLevels::new(Level::Trace),
+        Some(Ok(log_levels)) => log_levels,
+        Some(Err(e)) => {
+            eprintln!("Invalid debug-level: {}", e);
+            return;
+        }
+    };
+
+    // Logging
+    let decorator = slog_term::TermDecorator::new().build();
+    let drain = slog_term::CompactFormat::new(decorator).build().fuse();
+    let filter_levels = log_levels.clone();
+    let drain = drain
+        .filter(move |record| filter_levels.accepts(record))
+        .fuse();
+    let drain = slog_async::Async::new(drain).build().fuse();
+    let log = slog::Logger::root(drain, o!());
+
+    for module in log_levels.unknown_modules(SLOG_MODULES) {
+        warn!(log, "Log level has no effect outside of beacon_node"; "module" => module);
+    }
+
     let mut config = LighthouseConfig::default();
 
     // Custom datadir
@@ -63,41 +148,229 @@ fn main() {
         }
     }
 
+    // Weak subjectivity checkpoint
+    if let Some(checkpoint_str) = matches.value_of("wss-checkpoint") {
+        match parse_wss_checkpoint(checkpoint_str) {
+            Ok(checkpoint) => config.wss_checkpoint = Some(checkpoint),
+            Err(e) => {
+                error!(log, "Invalid weak subjectivity checkpoint"; "wss_checkpoint" => checkpoint_str, "error" => e);
+                return;
+            }
+        }
+    }
+
     // Log configuration
     info!(log, "";
           "data_dir" => &config.data_dir.to_str(),
           "port" => &config.p2p_listen_port);
 
+    // Genesis state exported by another node, if supplied.
+    let imported_genesis_state = match matches.value_of("import-genesis") {
+        Some(path) => match read_genesis_state(Path::new(path)) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                error!(log, "Unable to import genesis state"; "error" => e);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    // A generated genesis is different on every run, so nothing stored for it could be read
+    // after a restart. Only a chain from an imported genesis is stored on disk (along with its op
+    // pool and reorg history).
+    match imported_genesis_state {
+        Some(genesis_state) => {
+            let db = Arc::new(DiskDB::open(&config.data_dir, Some(&COLUMNS[..])));
+            run(&log, &matches, &config, db, Some(genesis_state))
+        }
+        None => run(&log, &matches, &config, Arc::new(MemoryDB::open()), None),
+    }
+}
+
+/// Start the beacon node upon `db` and run it until shutdown.
+fn run<T: ClientDB + 'static>(
+    log: &Logger,
+    matches: &ArgMatches,
+    config: &LighthouseConfig,
+    db: Arc<T>,
+    imported_genesis_state: Option<BeaconState>,
+) {
     // Specification (presently fixed to foundation).
     let spec = ChainSpec::foundation();
 
-    // Database (presently in-memory)
-    let db = Arc::new(MemoryDB::open());
+    // Database
     let block_store = Arc::new(BeaconBlockStore::new(db.clone()));
     let state_store = Arc::new(BeaconStateStore::new(db.clone()));
+    let op_pool_store = OpPoolStore::new(db.clone());
+    let reorg_store = ReorgStore::new(db.clone());
 
     // Slot clock
-    let genesis_time = 1_549_935_547; // 12th Feb 2018 (arbitrary value in the past).
-    let slot_clock = SystemTimeSlotClock::new(genesis_time, spec.slot_duration)
+    let genesis_time = match &imported_genesis_state {
+        Some(state) => state.genesis_time,
+        None => 1_549_935_547, // 12th Feb 2018 (arbitrary value in the past).
+    };
+    let slot_clock = SystemTimeSlotClock::new(spec.genesis_slot, genesis_time, spec.slot_duration)
         .expect("Unable to load SystemTimeSlotClock");
     // Choose the fork choice
     let fork_choice = BitwiseLMDGhost::new(block_store.clone(), state_store.clone());
 
-    /*
-     * Generate some random data to start a chain with.
-     *
-     * This is will need to be replace for production usage.
-     */
-    let latest_eth1_data = Eth1Data {
-        deposit_root: Hash256::zero(),
-        block_hash: Hash256::zero(),
+    // Genesis chain
+    let genesis_imported = imported_genesis_state.is_some();
+    let beacon_chain = match imported_genesis_state {
+        Some(genesis_state) => BeaconChain::from_genesis_state(
+            state_store.clone(),
+            block_store.clone(),
+            slot_clock,
+            genesis_state,
+            spec,
+            fork_choice,
+        ),
+        None => BeaconChain::genesis(
+            state_store.clone(),
+            block_store.clone(),
+            slot_clock,
+            genesis_time,
+            Eth1Data {
+                deposit_root: Hash256::zero(),
+                block_hash: Hash256::zero(),
+            },
+            random_genesis_deposits(genesis_time),
+            spec,
+            fork_choice,
+        ),
+    };
+    let beacon_chain = match beacon_chain {
+        Ok(beacon_chain) => beacon_chain,
+        Err(e) => {
+            error!(log, "Unable to create beacon chain"; "error" => format!("{:?}", e));
+            return;
+        }
     };
+
+    // Write the genesis state for other nodes to import.
+    if let Some(path) = matches.value_of("export-genesis") {
+        let written = beacon_chain
+            .genesis_state()
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|state| write_genesis_state(Path::new(path), &state));
+        match written {
+            Ok(()) => info!(log, "Exported genesis state"; "path" => path),
+            Err(e) => {
+                error!(log, "Unable to export genesis state"; "error" => e);
+                return;
+            }
+        }
+    }
+
+    // Restore any operations persisted before the last shutdown. These are only found if the node
+    // was previously run from the same imported genesis.
+    //
+    // TODO: the gRPC server does not yet pass attestations to the chain, so the op pool is only
+    // filled (and persisted) once it does.
+    if genesis_imported {
+        match beacon_chain.restore_op_pool(&op_pool_store) {
+            Ok(count) => info!(log, "Restored op pool"; "attestations" => count),
+            Err(e) => error!(log, "Unable to restore op pool"; "error" => format!("{:?}", e)),
+        }
+        match beacon_chain.restore_reorgs(&reorg_store) {
+            Ok(count) => info!(log, "Restored reorg history"; "reorgs" => count),
+            Err(e) => error!(log, "Unable to restore reorg history"; "error" => format!("{:?}", e)),
+        }
+    }
+
+    // Refuse to run on a chain which conflicts with the weak subjectivity checkpoint, and reject
+    // any later blocks which would.
+    if let Some((root, epoch)) = config.wss_checkpoint {
+        match beacon_chain.set_wss_checkpoint(root, epoch) {
+            Ok(true) => {
+                info!(log, "Verified weak subjectivity checkpoint"; "epoch" => epoch.as_u64())
+            }
+            Ok(false) => {
+                info!(log, "Weak subjectivity checkpoint will be verified when the chain reaches it";
+                      "epoch" => epoch.as_u64())
+            }
+            Err(BeaconChainError::EpochBeforeGenesis(_)) => {
+                error!(log, "Weak subjectivity checkpoint is before genesis";
+                       "epoch" => epoch.as_u64(),
+                       "genesis_epoch" => beacon_chain.spec.genesis_epoch.as_u64());
+                return;
+            }
+            Err(BeaconChainError::WeakSubjectivityConflict { canonical_root, .. }) => {
+                error!(log, "Canonical chain conflicts with weak subjectivity checkpoint";
+                       "epoch" => epoch.as_u64(),
+                       "expected_root" => format!("{:?}", root),
+                       "canonical_root" => format!("{:?}", canonical_root));
+                return;
+            }
+            Err(e) => {
+                error!(log, "Unable to verify weak subjectivity checkpoint"; "error" => format!("{:?}", e));
+                return;
+            }
+        }
+    }
+
+    // Refuse to run on an imported chain whose finalized state is too old to be safely built upon.
+    // A genesis generated by this node is trusted, regardless of its age.
+    if genesis_imported && !beacon_chain.is_within_weak_subjectivity_period() {
+        let finalized_epoch = beacon_chain
+            .finalized_head()
+            .beacon_state
+            .current_epoch(&beacon_chain.spec);
+        if matches.is_present("ignore-weak-subjectivity") {
+            warn!(log, "Finalized state is beyond the weak subjectivity period";
+                  "finalized_epoch" => finalized_epoch.as_u64());
+        } else {
+            error!(log, "Finalized state is beyond the weak subjectivity period, refusing to run. \
+                         Start from a recent trusted state or use --ignore-weak-subjectivity";
+                   "finalized_epoch" => finalized_epoch.as_u64());
+            return;
+        }
+    }
+
+    let _server = start_server(log.clone());
+
+    // Shutdown on SIGINT or SIGTERM.
+    let (shutdown_tx, shutdown_rx) = mpsc::channel();
+    if let Err(e) = ctrlc::set_handler(move || {
+        let _ = shutdown_tx.send(());
+    }) {
+        error!(log, "Unable to set shutdown handler"; "error" => format!("{}", e));
+        return;
+    }
+
+    loop {
+        let shutdown = match shutdown_rx.recv_timeout(Duration::from_secs(PERSIST_INTERVAL_SECS)) {
+            Err(RecvTimeoutError::Timeout) => false,
+            Ok(()) | Err(RecvTimeoutError::Disconnected) => true,
+        };
+
+        if genesis_imported {
+            if let Err(e) = beacon_chain.persist_op_pool(&op_pool_store) {
+                error!(log, "Unable to persist op pool"; "error" => format!("{:?}", e));
+            }
+            if let Err(e) = beacon_chain.persist_reorgs(&reorg_store) {
+                error!(log, "Unable to persist reorg history"; "error" => format!("{:?}", e));
+            }
+        }
+
+        if shutdown {
+            info!(log, "Shutting down");
+            return;
+        }
+    }
+}
+
+/// Generate deposits for a set of random validators to start a chain with.
+///
+/// This is will need to be replace for production usage.
+fn random_genesis_deposits(genesis_time: u64) -> Vec<Deposit> {
     let keypairs: Vec<Keypair> = (0..10)
         .collect::<Vec<usize>>()
         .iter()
         .map(|_| Keypair::random())
         .collect();
-    let initial_validator_deposits = keypairs
+    keypairs
         .iter()
         .map(|keypair| Deposit {
             branch: vec![], // branch verification is not specified.
@@ -112,23 +385,27 @@ fn main() {
                 },
             },
         })
-        .collect();
-
-    // Genesis chain
-    let _chain_result = BeaconChain::genesis(
-        state_store.clone(),
-        block_store.clone(),
-        slot_clock,
-        genesis_time,
-        latest_eth1_data,
-        initial_validator_deposits,
-        spec,
-        fork_choice,
-    );
-
-    let _server = start_server(log.clone());
+        .collect()
+}
 
-    loop {
-        std::thread::sleep(std::time::Duration::from_secs(1));
+/// Parse a weak subjectivity checkpoint in the form `ROOT:EPOCH`, where `ROOT` is a hex block root
+/// (optionally `0x` prefixed) and `EPOCH` is a decimal epoch.
+fn parse_wss_checkpoint(checkpoint: &str) -> Result<(Hash256, Epoch), String> {
+    let mut split = checkpoint.split(':');
+    match (split.next(), split.next(), split.next()) {
+        (Some(root), Some(epoch), None) => {
+            let root = root.trim_start_matches("0x");
+            if root.len() != 64 {
+                return Err("root must be 32 bytes of hex".to_string());
+            }
+            let root = root
+                .parse::<Hash256>()
+                .map_err(|_| "root must be 32 bytes of hex".to_string())?;
+            let epoch = epoch
+                .parse::<u64>()
+                .map_err(|_| "epoch must be an integer".to_string())?;
+            Ok((root, Epoch::new(epoch)))
+        }
+        _ => Err("expected ROOT:EPOCH".to_string()),
     }
 }
@@ -28,6 +28,8 @@ pub enum PollOutcome {
     SignerRejection(Slot),
     /// The public key for this validator is not an active validator.
     ValidatorIsUnknown(Slot),
+    /// The slot ended before the block could be signed and published, so it was abandoned.
+    BlockProductionDeadlineExceeded(Slot),
 }
 
 #[derive(Debug, PartialEq)]
@@ -148,7 +150,16 @@ impl<T: SlotClock, U: BeaconNode, V: DutiesReader, W: Signer> BlockProducer<T, U
             .produce_beacon_block(slot, &randao_reveal)?
         {
             if self.safe_to_produce(&block) {
+                if self.is_past_deadline(slot)? {
+                    return Ok(PollOutcome::BlockProductionDeadlineExceeded(slot));
+                }
+
                 if let Some(block) = self.sign_block(block) {
+                    // Signing may be slow (e.g., a remote signer), check the deadline again.
+                    if self.is_past_deadline(slot)? {
+                        return Ok(PollOutcome::BlockProductionDeadlineExceeded(slot));
+                    }
+
                     self.beacon_node.publish_beacon_block(block)?;
                     Ok(PollOutcome::BlockProduced(slot))
                 } else {
@@ -162,6 +173,20 @@ impl<T: SlotClock, U: BeaconNode, V: DutiesReader, W: Signer> BlockProducer<T, U
         }
     }
 
+    /// Returns `true` if the slot clock has moved beyond `slot`.
+    ///
+    /// A block for `slot` would be published late and should be abandoned rather than risk
+    /// confusing failures (or conflicting with a proposal made after a restart).
+    fn is_past_deadline(&self, slot: Slot) -> Result<bool, Error> {
+        let present_slot = self
+            .slot_clock
+            .present_slot()
+            .map_err(|_| Error::SlotClockError)?
+            .ok_or(Error::SlotUnknowable)?;
+
+        Ok(present_slot > slot)
+    }
+
     /// Consumes a block, returning that block signed by the validators private key.
     ///
     /// Important: this function will not check to ensure the block is not slashable. This must be
@@ -216,9 +241,34 @@ mod tests {
     use slot_clock::TestingSlotClock;
     use types::{
         test_utils::{SeedableRng, TestRandom, XorShiftRng},
-        Keypair,
+        Keypair, Signature,
     };
 
+    /// A `BeaconNode` which takes so long to produce a block that the slot clock advances.
+    struct SlowBeaconNode {
+        beacon_node: SimulatedBeaconNode,
+        slot_clock: Arc<TestingSlotClock>,
+    }
+
+    impl BeaconNode for SlowBeaconNode {
+        fn produce_beacon_block(
+            &self,
+            slot: Slot,
+            randao_reveal: &Signature,
+        ) -> Result<Option<BeaconBlock>, BeaconNodeError> {
+            let result = self.beacon_node.produce_beacon_block(slot, randao_reveal);
+            self.slot_clock.advance_slot();
+            result
+        }
+
+        fn publish_beacon_block(
+            &self,
+            block: BeaconBlock,
+        ) -> Result<PublishOutcome, BeaconNodeError> {
+            self.beacon_node.publish_beacon_block(block)
+        }
+    }
+
     // TODO: implement more thorough testing.
     // https://github.com/sigp/lighthouse/issues/160
     //
@@ -287,4 +337,44 @@ mod tests {
             Ok(PollOutcome::ProducerDutiesUnknown(Slot::new(slot)))
         );
     }
+
+    #[test]
+    pub fn late_block_is_not_published() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+
+        let spec = Arc::new(ChainSpec::foundation());
+        let produce_slot = Slot::new(100);
+        let slot_clock = Arc::new(TestingSlotClock::new(produce_slot.as_u64()));
+        let beacon_node = Arc::new(SlowBeaconNode {
+            beacon_node: SimulatedBeaconNode::default(),
+            slot_clock: slot_clock.clone(),
+        });
+        let signer = Arc::new(LocalSigner::new(Keypair::random()));
+
+        let mut epoch_map = EpochMap::new(spec.epoch_length);
+        epoch_map
+            .map
+            .insert(produce_slot.epoch(spec.epoch_length), produce_slot);
+
+        let mut block_proposer = BlockProducer::new(
+            spec.clone(),
+            Arc::new(epoch_map),
+            slot_clock.clone(),
+            beacon_node.clone(),
+            signer.clone(),
+        );
+
+        beacon_node
+            .beacon_node
+            .set_next_produce_result(Ok(Some(BeaconBlock::random_for_test(&mut rng))));
+        beacon_node
+            .beacon_node
+            .set_next_publish_result(Ok(PublishOutcome::ValidBlock));
+
+        assert_eq!(
+            block_proposer.poll(),
+            Ok(PollOutcome::BlockProductionDeadlineExceeded(produce_slot))
+        );
+        assert_eq!(*beacon_node.beacon_node.publish_input.read().unwrap(), None);
+    }
 }
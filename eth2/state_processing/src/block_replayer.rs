@@ -0,0 +1,127 @@
+use crate::{BlockProcessable, BlockProcessingError, SlotProcessable, SlotProcessingError};
+use types::{BeaconBlock, BeaconState, ChainSpec, Hash256, Slot};
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// There was an error whilst advancing the state through empty slots.
+    SlotProcessingError(SlotProcessingError),
+    /// The block could not be applied to the state.
+    BlockProcessingError(BlockProcessingError),
+    /// The block `state_root` does not match the state generated by applying the block.
+    StateRootMismatch { slot: Slot },
+}
+
+/// Applies a sequence of blocks (and the empty slots between them) to a state.
+///
+/// All replays of blocks should go through this struct so that the per-slot and per-block
+/// transitions are applied consistently, regardless of which checks the caller requires.
+///
+/// By default, both block signatures and state roots are verified.
+pub struct BlockReplayer<'a> {
+    state: BeaconState,
+    spec: &'a ChainSpec,
+    verify_block_signatures: bool,
+    verify_state_roots: bool,
+    post_block_hook: Option<Box<dyn FnMut(&BeaconState, &BeaconBlock) + 'a>>,
+}
+
+impl<'a> BlockReplayer<'a> {
+    /// Create a new replayer which will apply blocks to `state`.
+    pub fn new(state: BeaconState, spec: &'a ChainSpec) -> Self {
+        Self {
+            state,
+            spec,
+            verify_block_signatures: true,
+            verify_state_roots: true,
+            post_block_hook: None,
+        }
+    }
+
+    /// Do not verify the block proposer signature of the replayed blocks.
+    ///
+    /// Useful for blocks which are known to be valid (e.g., read from the database) or which have
+    /// not yet been signed.
+    pub fn no_block_signature_verification(mut self) -> Self {
+        self.verify_block_signatures = false;
+        self
+    }
+
+    /// Do not check each replayed block's `state_root` against the resulting state.
+    pub fn no_state_root_verification(mut self) -> Self {
+        self.verify_state_roots = false;
+        self
+    }
+
+    /// Call `hook` with the resulting state each time a block is applied.
+    pub fn post_block_hook<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(&BeaconState, &BeaconBlock) + 'a,
+    {
+        self.post_block_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Advance the state through empty slots until it reaches `slot`.
+    ///
+    /// `previous_block_root` is the root of the latest block applied to the state. Does nothing
+    /// if the state is already at or beyond `slot`.
+    pub fn apply_slots(&mut self, previous_block_root: Hash256, slot: Slot) -> Result<(), Error> {
+        while self.state.slot < slot {
+            self.state
+                .per_slot_processing(previous_block_root, self.spec)?;
+        }
+        Ok(())
+    }
+
+    /// Advance the state to the slot of `block`, then apply `block` to it.
+    pub fn apply_block(&mut self, block: &BeaconBlock) -> Result<(), Error> {
+        self.apply_slots(block.parent_root, block.slot)?;
+
+        if self.verify_block_signatures {
+            self.state.per_block_processing(block, self.spec)?;
+        } else {
+            self.state
+                .per_block_processing_without_verifying_block_signature(block, self.spec)?;
+        }
+
+        if self.verify_state_roots && block.state_root != self.state.canonical_root() {
+            return Err(Error::StateRootMismatch { slot: block.slot });
+        }
+
+        if let Some(hook) = self.post_block_hook.as_mut() {
+            hook(&self.state, block);
+        }
+
+        Ok(())
+    }
+
+    /// Apply each of `blocks`, in order.
+    pub fn apply_blocks(&mut self, blocks: &[BeaconBlock]) -> Result<(), Error> {
+        for block in blocks {
+            self.apply_block(block)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the state, as it stands after all applied slots and blocks.
+    pub fn state(&self) -> &BeaconState {
+        &self.state
+    }
+
+    /// Consumes `self`, returning the state.
+    pub fn into_state(self) -> BeaconState {
+        self.state
+    }
+}
+
+impl From<SlotProcessingError> for Error {
+    fn from(e: SlotProcessingError) -> Error {
+        Error::SlotProcessingError(e)
+    }
+}
+
+impl From<BlockProcessingError> for Error {
+    fn from(e: BlockProcessingError) -> Error {
+        Error::BlockProcessingError(e)
+    }
+}
@@ -1,4 +1,5 @@
 mod block_processable;
+mod block_replayer;
 mod epoch_processable;
 mod slot_processable;
 
@@ -6,5 +7,6 @@ pub use block_processable::{
     validate_attestation, validate_attestation_without_signature, BlockProcessable,
     Error as BlockProcessingError,
 };
+pub use block_replayer::{BlockReplayer, Error as BlockReplayError};
 pub use epoch_processable::{EpochProcessable, Error as EpochProcessingError};
 pub use slot_processable::{Error as SlotProcessingError, SlotProcessable};
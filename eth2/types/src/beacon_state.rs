@@ -27,6 +27,11 @@ pub type ShardCommitteeIndexMap = HashMap<Shard, (usize, usize)>;
 
 pub const CACHED_EPOCHS: usize = 3;
 
+const ETH_TO_GWEI: u64 = 1_000_000_000;
+/// The maximum tolerated loss of safety margin (in percent) used when computing the weak
+/// subjectivity period.
+const WEAK_SUBJECTIVITY_SAFETY_DECAY: u64 = 10;
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RelativeEpoch {
     Previous,
@@ -1112,6 +1117,49 @@ impl BeaconState {
             .fold(0, |acc, i| acc + self.get_effective_balance(*i, spec))
     }
 
+    /// Returns the weak subjectivity period of `self`: the number of epochs after the current epoch
+    /// during which it is safe to sync a chain starting from `self`.
+    ///
+    /// Follows `compute_weak_subjectivity_period` from the phase 0 weak subjectivity guide. The
+    /// v0.2.0 spec limits activations by balance rather than by validator count, so the
+    /// validator churn limit is taken as the number of maximum deposits that fit in the balance
+    /// churn.
+    pub fn weak_subjectivity_period(&self, spec: &ChainSpec) -> Epoch {
+        let mut ws_period = spec.min_validator_withdrawal_epochs;
+
+        let active_validator_indices =
+            get_active_validator_indices(&self.validator_registry, self.current_epoch(spec));
+        let n = active_validator_indices.len() as u64;
+        if n == 0 {
+            return ws_period;
+        }
+
+        let total_balance = self.get_total_balance(&active_validator_indices[..], spec);
+        let max_balance_churn = std::cmp::max(
+            spec.max_deposit_amount,
+            total_balance / (2 * spec.max_balance_churn_quotient),
+        );
+
+        // Average and maximum effective balance, in ETH.
+        let t = total_balance / n / ETH_TO_GWEI;
+        let max_t = spec.max_deposit_amount / ETH_TO_GWEI;
+        // Validator churn limit and maximum balance top-ups per epoch.
+        let delta = std::cmp::max(1, max_balance_churn / spec.max_deposit_amount);
+        let max_delta = spec.max_deposits * spec.epoch_length;
+        let d = WEAK_SUBJECTIVITY_SAFETY_DECAY;
+
+        if max_t * (200 + 3 * d) < t * (200 + 12 * d) {
+            let epochs_for_validator_set_churn =
+                n * (t * (200 + 12 * d) - max_t * (200 + 3 * d)) / (600 * delta * (2 * t + max_t));
+            let epochs_for_balance_top_ups = n * (200 + 3 * d) / (600 * max_delta);
+            ws_period += std::cmp::max(epochs_for_validator_set_churn, epochs_for_balance_top_ups);
+        } else {
+            ws_period += 3 * n * d * t / (200 * max_delta * (max_t - t));
+        }
+
+        ws_period
+    }
+
     /// Return the effective balance (also known as "balance at stake") for a validator with the given ``index``.
     ///
     /// Spec v0.2.0
@@ -73,6 +73,39 @@ pub fn can_produce_genesis_block() {
     builder.build().unwrap();
 }
 
+#[test]
+pub fn weak_subjectivity_period() {
+    let builder = BeaconStateTestBuilder::with_random_validators(2);
+    let mut state = builder.build().unwrap();
+    let spec = &builder.spec;
+
+    // Too few validators to extend the period beyond the withdrawal delay.
+    assert_eq!(
+        state.weak_subjectivity_period(spec),
+        spec.min_validator_withdrawal_epochs
+    );
+
+    // Lower balances make the validator set cheaper to churn, but the period never falls below
+    // the withdrawal delay.
+    for balance in state.validator_balances.iter_mut() {
+        *balance = 16_000_000_000;
+    }
+    assert_eq!(
+        state.weak_subjectivity_period(spec),
+        spec.min_validator_withdrawal_epochs
+    );
+
+    // A large validator set at full balance extends the period by the epochs required for
+    // balance top-ups.
+    let validator = state.validator_registry[0].clone();
+    state.validator_registry = vec![validator; 100_000];
+    state.validator_balances = vec![32_000_000_000; 100_000];
+    assert_eq!(
+        state.weak_subjectivity_period(spec),
+        spec.min_validator_withdrawal_epochs + 37
+    );
+}
+
 /// Tests that `get_attestation_participants` is consistent with the result of
 /// get_crosslink_committees_at_slot` with a full bitfield.
 #[test]
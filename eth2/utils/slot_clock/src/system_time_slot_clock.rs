@@ -11,8 +11,12 @@ pub enum Error {
 }
 
 /// Determines the present slot based upon the present system time.
+///
+/// The slot at `genesis_seconds` is `genesis_slot`, so that slots are numbered as they are in the
+/// `BeaconState`.
 #[derive(Clone)]
 pub struct SystemTimeSlotClock {
+    genesis_slot: Slot,
     genesis_seconds: u64,
     slot_duration_seconds: u64,
 }
@@ -22,6 +26,7 @@ impl SystemTimeSlotClock {
     ///
     /// Returns an Error if `slot_duration_seconds == 0`.
     pub fn new(
+        genesis_slot: Slot,
         genesis_seconds: u64,
         slot_duration_seconds: u64,
     ) -> Result<SystemTimeSlotClock, Error> {
@@ -29,6 +34,7 @@ impl SystemTimeSlotClock {
             Err(Error::SlotDurationIsZero)
         } else {
             Ok(Self {
+                genesis_slot,
                 genesis_seconds,
                 slot_duration_seconds,
             })
@@ -46,7 +52,8 @@ impl SlotClock for SystemTimeSlotClock {
             duration_since_epoch.checked_sub(Duration::from_secs(self.genesis_seconds));
         match duration_since_genesis {
             None => Ok(None),
-            Some(d) => Ok(slot_from_duration(self.slot_duration_seconds, d)),
+            Some(d) => Ok(slot_from_duration(self.slot_duration_seconds, d)
+                .map(|slot| slot + self.genesis_slot)),
         }
     }
 }
@@ -81,22 +88,28 @@ mod tests {
         let genesis = since_epoch.as_secs() - slot_time * 89;
 
         let clock = SystemTimeSlotClock {
+            genesis_slot: Slot::new(0),
             genesis_seconds: genesis,
             slot_duration_seconds: slot_time,
         };
         assert_eq!(clock.present_slot().unwrap(), Some(Slot::new(89)));
 
         let clock = SystemTimeSlotClock {
+            genesis_slot: Slot::new(0),
             genesis_seconds: since_epoch.as_secs(),
             slot_duration_seconds: slot_time,
         };
         assert_eq!(clock.present_slot().unwrap(), Some(Slot::new(0)));
 
         let clock = SystemTimeSlotClock {
+            genesis_slot: Slot::new(0),
             genesis_seconds: since_epoch.as_secs() - slot_time * 42 - 5,
             slot_duration_seconds: slot_time,
         };
         assert_eq!(clock.present_slot().unwrap(), Some(Slot::new(42)));
+
+        let clock = SystemTimeSlotClock::new(Slot::new(1_000), genesis, slot_time).unwrap();
+        assert_eq!(clock.present_slot().unwrap(), Some(Slot::new(1_089)));
     }
 
     #[test]
@@ -23,6 +23,15 @@ impl TestingSlotClock {
     pub fn set_slot(&self, slot: u64) {
         *self.slot.write().expect("TestingSlotClock poisoned.") = slot;
     }
+
+    /// Move the clock forward one slot, returning the new slot.
+    ///
+    /// Allows tests to step through slots deterministically, instead of waiting on a system clock.
+    pub fn advance_slot(&self) -> Slot {
+        let mut slot = self.slot.write().expect("TestingSlotClock poisoned.");
+        *slot += 1;
+        Slot::new(*slot)
+    }
 }
 
 impl SlotClock for TestingSlotClock {
@@ -44,5 +53,7 @@ mod tests {
         assert_eq!(clock.present_slot(), Ok(Some(Slot::new(10))));
         clock.set_slot(123);
         assert_eq!(clock.present_slot(), Ok(Some(Slot::new(123))));
+        assert_eq!(clock.advance_slot(), Slot::new(124));
+        assert_eq!(clock.present_slot(), Ok(Some(Slot::new(124))));
     }
 }
@@ -0,0 +1,295 @@
+use block_proposer::{BeaconNode, BeaconNodeError, PublishOutcome};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use types::{BeaconBlock, Signature, Slot};
+
+/// Wraps several Beacon Nodes (BNs) so that a block may be published to all of them.
+///
+/// Blocks are produced by the first BN that is able to, trying each in the order supplied. Blocks
+/// are published to every BN concurrently and the publish is only considered successful once at
+/// least `quorum` of them have accepted the block within `publish_timeout`.
+pub struct BroadcastBeaconNode<T: BeaconNode> {
+    nodes: Vec<Arc<T>>,
+    quorum: usize,
+    publish_timeout: Duration,
+}
+
+impl<T: BeaconNode> BroadcastBeaconNode<T> {
+    /// Create a new instance.
+    ///
+    /// It is the responsibility of the caller to ensure that `0 < quorum <= nodes.len()`,
+    /// otherwise no publish can ever succeed.
+    pub fn new(nodes: Vec<Arc<T>>, quorum: usize, publish_timeout: Duration) -> Self {
+        Self {
+            nodes,
+            quorum,
+            publish_timeout,
+        }
+    }
+}
+
+impl<T: BeaconNode + 'static> BeaconNode for BroadcastBeaconNode<T> {
+    /// Request each BN to produce a block, in turn, returning the first block produced.
+    ///
+    /// Returns `Ok(None)` if no BN produced a block and at least one BN indicated it was unable to
+    /// produce at the given slot. Otherwise, if all BNs failed, the errors are combined.
+    fn produce_beacon_block(
+        &self,
+        slot: Slot,
+        randao_reveal: &Signature,
+    ) -> Result<Option<BeaconBlock>, BeaconNodeError> {
+        let mut unable_to_produce = false;
+        let mut errors = vec![];
+
+        for node in &self.nodes {
+            match node.produce_beacon_block(slot, randao_reveal) {
+                Ok(Some(block)) => return Ok(Some(block)),
+                Ok(None) => unable_to_produce = true,
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if unable_to_produce {
+            Ok(None)
+        } else {
+            Err(combine_errors(errors))
+        }
+    }
+
+    /// Publish the block to every BN, concurrently, waiting at most `publish_timeout` for them to
+    /// respond.
+    ///
+    /// Returns `ValidBlock` if at least `quorum` BNs accepted the block. Otherwise, returns
+    /// `InvalidBlock` if any BN rejected the block, or an error if no BN could be reached (a BN
+    /// which did not respond in time is treated as unreachable). Identical rejection reasons and
+    /// errors from multiple BNs are reported once.
+    fn publish_beacon_block(&self, block: BeaconBlock) -> Result<PublishOutcome, BeaconNodeError> {
+        let (tx, rx) = mpsc::channel();
+        for (i, node) in self.nodes.iter().enumerate() {
+            let node = node.clone();
+            let block = block.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                // The receiver is gone if the publish has already timed out.
+                let _ = tx.send((i, node.publish_beacon_block(block)));
+            });
+        }
+
+        let deadline = Instant::now() + self.publish_timeout;
+        let mut results: Vec<Option<Result<PublishOutcome, BeaconNodeError>>> =
+            self.nodes.iter().map(|_| None).collect();
+        for _ in 0..self.nodes.len() {
+            let now = Instant::now();
+            let timeout = if deadline > now {
+                deadline - now
+            } else {
+                Duration::from_secs(0)
+            };
+            match rx.recv_timeout(timeout) {
+                Ok((i, result)) => results[i] = Some(result),
+                Err(_) => break,
+            }
+        }
+
+        let mut accepted = 0;
+        let mut rejections: Vec<String> = vec![];
+        let mut errors = vec![];
+
+        // Results are considered in the order the BNs were supplied, regardless of the order in
+        // which they responded.
+        for result in results {
+            match result {
+                Some(Ok(PublishOutcome::ValidBlock)) => accepted += 1,
+                Some(Ok(PublishOutcome::InvalidBlock(reason))) => {
+                    if !rejections.contains(&reason) {
+                        rejections.push(reason)
+                    }
+                }
+                Some(Err(e)) => errors.push(e),
+                None => errors.push(BeaconNodeError::RemoteFailure(
+                    "Publish timed out".to_string(),
+                )),
+            }
+        }
+
+        if accepted >= self.quorum {
+            Ok(PublishOutcome::ValidBlock)
+        } else if !rejections.is_empty() {
+            Ok(PublishOutcome::InvalidBlock(format!(
+                "{} of {} required beacon nodes accepted the block: {}",
+                accepted,
+                self.quorum,
+                rejections.join(", ")
+            )))
+        } else {
+            Err(combine_errors(errors))
+        }
+    }
+}
+
+/// Combine the errors from several BNs into a single error, listing each distinct error once.
+fn combine_errors(errors: Vec<BeaconNodeError>) -> BeaconNodeError {
+    if errors.len() == 1 {
+        return errors[0].clone();
+    }
+
+    let mut messages: Vec<String> = vec![];
+    for error in errors {
+        let message = format!("{:?}", error);
+        if !messages.contains(&message) {
+            messages.push(message)
+        }
+    }
+
+    BeaconNodeError::RemoteFailure(messages.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use block_proposer::test_utils::SimulatedBeaconNode;
+    use types::test_utils::{SeedableRng, TestRandom, XorShiftRng};
+
+    const PUBLISH_TIMEOUT: Duration = Duration::from_millis(500);
+
+    fn nodes(count: usize) -> Vec<Arc<SimulatedBeaconNode>> {
+        (0..count)
+            .map(|_| Arc::new(SimulatedBeaconNode::default()))
+            .collect()
+    }
+
+    /// A `SimulatedBeaconNode` which takes `delay` to respond to a publish.
+    struct SlowBeaconNode {
+        node: SimulatedBeaconNode,
+        delay: Duration,
+    }
+
+    impl BeaconNode for SlowBeaconNode {
+        fn produce_beacon_block(
+            &self,
+            slot: Slot,
+            randao_reveal: &Signature,
+        ) -> Result<Option<BeaconBlock>, BeaconNodeError> {
+            self.node.produce_beacon_block(slot, randao_reveal)
+        }
+
+        fn publish_beacon_block(
+            &self,
+            block: BeaconBlock,
+        ) -> Result<PublishOutcome, BeaconNodeError> {
+            thread::sleep(self.delay);
+            self.node.publish_beacon_block(block)
+        }
+    }
+
+    #[test]
+    pub fn publish_quorum() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let block = BeaconBlock::random_for_test(&mut rng);
+
+        let nodes = nodes(3);
+        nodes[0].set_next_publish_result(Ok(PublishOutcome::ValidBlock));
+        nodes[1].set_next_publish_result(Ok(PublishOutcome::InvalidBlock("bad".to_string())));
+        nodes[2].set_next_publish_result(Ok(PublishOutcome::ValidBlock));
+
+        // Two of the three nodes accept the block.
+        let broadcast = BroadcastBeaconNode::new(nodes.clone(), 2, PUBLISH_TIMEOUT);
+        assert_eq!(
+            broadcast.publish_beacon_block(block.clone()),
+            Ok(PublishOutcome::ValidBlock)
+        );
+
+        // Requiring all three nodes is not satisfied.
+        let broadcast = BroadcastBeaconNode::new(nodes.clone(), 3, PUBLISH_TIMEOUT);
+        assert_eq!(
+            broadcast.publish_beacon_block(block.clone()),
+            Ok(PublishOutcome::InvalidBlock(
+                "2 of 3 required beacon nodes accepted the block: bad".to_string()
+            ))
+        );
+
+        // Every node was sent the block.
+        for node in &nodes {
+            assert_eq!(*node.publish_input.read().unwrap(), Some(block.clone()));
+        }
+    }
+
+    #[test]
+    pub fn publish_errors_are_deduplicated() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let block = BeaconBlock::random_for_test(&mut rng);
+
+        let nodes = nodes(3);
+        nodes[0].set_next_publish_result(Err(BeaconNodeError::DecodeFailure));
+        nodes[1].set_next_publish_result(Err(BeaconNodeError::DecodeFailure));
+        nodes[2].set_next_publish_result(Err(BeaconNodeError::RemoteFailure("down".to_string())));
+
+        let broadcast = BroadcastBeaconNode::new(nodes, 1, PUBLISH_TIMEOUT);
+        assert_eq!(
+            broadcast.publish_beacon_block(block),
+            Err(BeaconNodeError::RemoteFailure(
+                "DecodeFailure, RemoteFailure(\"down\")".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    pub fn publish_does_not_wait_for_a_hung_node() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let block = BeaconBlock::random_for_test(&mut rng);
+
+        let node = |delay| {
+            let node = SimulatedBeaconNode::default();
+            node.set_next_publish_result(Ok(PublishOutcome::ValidBlock));
+            Arc::new(SlowBeaconNode { node, delay })
+        };
+        let nodes = vec![node(Duration::from_secs(60)), node(Duration::from_secs(0))];
+
+        // The hung node does not prevent the block from reaching the other.
+        let broadcast = BroadcastBeaconNode::new(nodes.clone(), 1, PUBLISH_TIMEOUT);
+        let start = Instant::now();
+        assert_eq!(
+            broadcast.publish_beacon_block(block.clone()),
+            Ok(PublishOutcome::ValidBlock)
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(
+            *nodes[1].node.publish_input.read().unwrap(),
+            Some(block.clone())
+        );
+
+        // The hung node is not counted towards the quorum.
+        let broadcast = BroadcastBeaconNode::new(nodes, 2, PUBLISH_TIMEOUT);
+        assert_eq!(
+            broadcast.publish_beacon_block(block),
+            Err(BeaconNodeError::RemoteFailure(
+                "Publish timed out".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    pub fn produce_falls_back() {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let block = BeaconBlock::random_for_test(&mut rng);
+        let randao_reveal = Signature::random_for_test(&mut rng);
+
+        let nodes = nodes(2);
+        nodes[0].set_next_produce_result(Err(BeaconNodeError::DecodeFailure));
+        nodes[1].set_next_produce_result(Ok(Some(block.clone())));
+
+        let broadcast = BroadcastBeaconNode::new(nodes.clone(), 1, PUBLISH_TIMEOUT);
+        assert_eq!(
+            broadcast.produce_beacon_block(block.slot, &randao_reveal),
+            Ok(Some(block.clone()))
+        );
+
+        nodes[1].set_next_produce_result(Ok(None));
+        assert_eq!(
+            broadcast.produce_beacon_block(block.slot, &randao_reveal),
+            Ok(None)
+        );
+    }
+}
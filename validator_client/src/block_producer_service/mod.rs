@@ -1,4 +1,5 @@
 mod beacon_block_grpc_client;
+mod broadcast_beacon_node;
 // mod block_producer_service;
 
 use block_proposer::{
@@ -9,6 +10,7 @@ use slot_clock::SlotClock;
 use std::time::Duration;
 
 pub use self::beacon_block_grpc_client::BeaconBlockGrpcClient;
+pub use self::broadcast_beacon_node::BroadcastBeaconNode;
 
 pub struct BlockProducerService<T: SlotClock, U: BeaconNode, V: DutiesReader, W: Signer> {
     pub block_producer: BlockProducer<T, U, V, W>,
@@ -50,6 +52,9 @@ impl<T: SlotClock, U: BeaconNode, V: DutiesReader, W: Signer> BlockProducerServi
                 Ok(BlockProducerPollOutcome::ValidatorIsUnknown(slot)) => {
                     error!(self.log, "The Beacon Node does not recognise the validator"; "slot" => slot)
                 }
+                Ok(BlockProducerPollOutcome::BlockProductionDeadlineExceeded(slot)) => {
+                    error!(self.log, "Block production overran its slot, block was not published"; "slot" => slot)
+                }
             };
 
             std::thread::sleep(Duration::from_millis(self.poll_interval_millis));
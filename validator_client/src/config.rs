@@ -5,7 +5,10 @@ use std::path::PathBuf;
 #[derive(Clone)]
 pub struct ClientConfig {
     pub data_dir: PathBuf,
-    pub server: String,
+    /// Beacon nodes to connect to. Duties are read from the first; blocks are published to all.
+    pub servers: Vec<String>,
+    /// The number of beacon nodes which must accept a block for it to be considered published.
+    pub publish_quorum: usize,
 }
 
 const DEFAULT_LIGHTHOUSE_DIR: &str = ".lighthouse-validators";
@@ -19,7 +22,11 @@ impl ClientConfig {
         };
         fs::create_dir_all(&data_dir)
             .unwrap_or_else(|_| panic!("Unable to create {:?}", &data_dir));
-        let server = "localhost:50051".to_string();
-        Self { data_dir, server }
+        let servers = vec!["localhost:50051".to_string()];
+        Self {
+            data_dir,
+            servers,
+            publish_quorum: 1,
+        }
     }
 }
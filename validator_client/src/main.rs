@@ -1,4 +1,6 @@
-use self::block_producer_service::{BeaconBlockGrpcClient, BlockProducerService};
+use self::block_producer_service::{
+    BeaconBlockGrpcClient, BlockProducerService, BroadcastBeaconNode,
+};
 use self::duties::{DutiesManager, DutiesManagerService, EpochDutiesMap};
 use crate::config::ClientConfig;
 use block_proposer::{test_utils::LocalSigner, BlockProducer};
@@ -11,6 +13,7 @@ use slot_clock::SystemTimeSlotClock;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 use types::ChainSpec;
 
 mod block_producer_service;
@@ -40,7 +43,16 @@ fn main() {
             Arg::with_name("server")
                 .long("server")
                 .value_name("server")
-                .help("Address to connect to BeaconNode.")
+                .help("Address to connect to BeaconNode. May be supplied multiple times.")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("publish-quorum")
+                .long("publish-quorum")
+                .value_name("COUNT")
+                .help("Number of BeaconNodes which must accept a block for it to be published.")
                 .takes_value(true),
         )
         .get_matches();
@@ -52,32 +64,47 @@ fn main() {
         config.data_dir = PathBuf::from(dir.to_string());
     }
 
-    // Custom server port
-    if let Some(server_str) = matches.value_of("server") {
-        if let Ok(addr) = server_str.parse::<u16>() {
-            config.server = addr.to_string();
-        } else {
-            error!(log, "Invalid address"; "server" => server_str);
-            return;
+    // Custom servers
+    if let Some(servers) = matches.values_of("server") {
+        config.servers = servers.map(|server| server.to_string()).collect();
+    }
+
+    // Custom publish quorum
+    if let Some(quorum_str) = matches.value_of("publish-quorum") {
+        match quorum_str.parse::<usize>() {
+            Ok(quorum) if quorum > 0 && quorum <= config.servers.len() => {
+                config.publish_quorum = quorum
+            }
+            _ => {
+                error!(log, "Invalid publish quorum"; "publish_quorum" => quorum_str, "servers" => config.servers.len());
+                return;
+            }
         }
     }
 
     // Log configuration
     info!(log, "";
           "data_dir" => &config.data_dir.to_str(),
-          "server" => &config.server);
-
-    // Beacon node gRPC beacon block endpoints.
-    let beacon_block_grpc_client = {
-        let env = Arc::new(EnvBuilder::new().build());
-        let ch = ChannelBuilder::new(env).connect(&config.server);
-        Arc::new(BeaconBlockServiceClient::new(ch))
-    };
+          "servers" => format!("{:?}", config.servers),
+          "publish_quorum" => config.publish_quorum);
+
+    // Beacon node gRPC beacon block endpoints, one per server.
+    let beacon_block_grpc_clients: Vec<Arc<BeaconBlockGrpcClient>> = config
+        .servers
+        .iter()
+        .map(|server| {
+            let env = Arc::new(EnvBuilder::new().build());
+            let ch = ChannelBuilder::new(env).connect(server);
+            Arc::new(BeaconBlockGrpcClient::new(Arc::new(
+                BeaconBlockServiceClient::new(ch),
+            )))
+        })
+        .collect();
 
     // Beacon node gRPC validator endpoints.
     let validator_grpc_client = {
         let env = Arc::new(EnvBuilder::new().build());
-        let ch = ChannelBuilder::new(env).connect(&config.server);
+        let ch = ChannelBuilder::new(env).connect(&config.servers[0]);
         Arc::new(ValidatorServiceClient::new(ch))
     };
 
@@ -93,7 +120,7 @@ fn main() {
     let genesis_time = 1_549_935_547;
     let slot_clock = {
         info!(log, "Genesis time"; "unix_epoch_seconds" => genesis_time);
-        let clock = SystemTimeSlotClock::new(genesis_time, spec.slot_duration)
+        let clock = SystemTimeSlotClock::new(spec.genesis_slot, genesis_time, spec.slot_duration)
             .expect("Unable to instantiate SystemTimeSlotClock.");
         Arc::new(clock)
     };
@@ -146,7 +173,12 @@ fn main() {
             let duties_map = duties_map.clone();
             let slot_clock = slot_clock.clone();
             let log = log.clone();
-            let client = Arc::new(BeaconBlockGrpcClient::new(beacon_block_grpc_client.clone()));
+            // A block published after its slot has ended is of little use.
+            let client = Arc::new(BroadcastBeaconNode::new(
+                beacon_block_grpc_clients.clone(),
+                config.publish_quorum,
+                Duration::from_secs(spec.slot_duration),
+            ));
             thread::spawn(move || {
                 let block_producer =
                     BlockProducer::new(spec, duties_map, slot_clock, client, signer);